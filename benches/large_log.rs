@@ -0,0 +1,116 @@
+//! Parses a synthetic `cargo test` log of 1 GiB, and halves of it, and fails
+//! unless parsing time grows linearly with the size of the log. Run with
+//! `cargo bench --bench large_log`; set `LARGE_LOG_BYTES` to change the size.
+
+extern crate cargo_results;
+
+use std::env;
+use std::io::Write;
+use std::time::Instant;
+
+use cargo_results::borrowed;
+
+const DEFAULT_BYTES: usize = 1 << 30;
+/// How much slower per byte the whole log may parse than its eighth before
+/// the growth no longer counts as linear. Quadratic growth would be 8 times.
+const MAX_SLOWDOWN: f64 = 2.0;
+/// Each size is parsed this many times and the fastest run counts, to keep
+/// other load on the machine out of the comparison.
+const RUNS: usize = 3;
+
+fn write_suite(log: &mut Vec<u8>, n: usize) {
+    let tests = 200;
+    let failed = 20;
+
+    writeln!(log, "     Running tests/stress_{}.rs (target/debug/deps/stress_{}-0b1c2d3e4f5a6b7c)\n", n, n).unwrap();
+    writeln!(log, "running {} tests", tests).unwrap();
+    for i in 0..tests {
+        let status = if i < failed { "FAILED" } else { "ok" };
+        writeln!(log, "test stress::case_{} ... {}", i, status).unwrap();
+    }
+
+    log.extend_from_slice(b"\nfailures:\n\n");
+    for i in 0..failed {
+        writeln!(log, "---- stress::case_{} stdout ----", i).unwrap();
+        writeln!(log, "thread 'stress::case_{}' panicked at tests/stress_{}.rs:{}:9:", i, n, i).unwrap();
+        for line in 0..40 {
+            writeln!(log, "  iteration {:>4}: expected {} but the state was {}", line, line * 3, line * 7).unwrap();
+        }
+        log.extend_from_slice(b"\n\n");
+    }
+
+    log.extend_from_slice(b"\nfailures:\n");
+    for i in 0..failed {
+        writeln!(log, "    stress::case_{}", i).unwrap();
+    }
+    writeln!(
+        log,
+        "\ntest result: FAILED. {} passed; {} failed; 0 ignored; 0 measured; 0 filtered out; finished in 1.25s\n",
+        tests - failed,
+        failed
+    )
+    .unwrap();
+}
+
+fn synthetic_log(bytes: usize) -> Vec<u8> {
+    let mut log = Vec::with_capacity(bytes + (1 << 20));
+
+    log.extend_from_slice(b"   Compiling stress v0.1.0 (/work/stress)\n");
+    log.extend_from_slice(b"    Finished test [unoptimized + debuginfo] target(s) in 9.81s\n");
+    let mut n = 0;
+    while log.len() < bytes {
+        write_suite(&mut log, n);
+        n += 1;
+    }
+
+    log
+}
+
+fn main() {
+    let bytes = env::var("LARGE_LOG_BYTES")
+        .ok()
+        .and_then(|b| b.parse().ok())
+        .unwrap_or(DEFAULT_BYTES);
+    let log = synthetic_log(bytes);
+
+    let mut throughputs = vec![];
+
+    for &fraction in &[8, 4, 2, 1] {
+        let part = &log[..suite_boundary(&log, log.len() / fraction)];
+        let mut seconds = f64::INFINITY;
+        let mut suites = 0;
+        for _ in 0..RUNS {
+            let start = Instant::now();
+            suites = borrowed::parse(part).expect("the synthetic log parses").len();
+            seconds = seconds.min(start.elapsed().as_secs_f64());
+        }
+        let megabytes = part.len() as f64 / (1 << 20) as f64;
+
+        println!(
+            "{:>8.1} MiB, {:>6} suites: {:>7.3}s, {:>7.1} MiB/s",
+            megabytes,
+            suites,
+            seconds,
+            megabytes / seconds
+        );
+        throughputs.push(megabytes / seconds);
+    }
+
+    let slowdown = throughputs[0] / throughputs[throughputs.len() - 1];
+    assert!(
+        slowdown < MAX_SLOWDOWN,
+        "the whole log parsed {:.2} times slower per byte than its eighth",
+        slowdown
+    );
+}
+
+/// The start of the first suite at or after `at`, so every prefix is a
+/// complete log.
+fn suite_boundary(log: &[u8], at: usize) -> usize {
+    let marker = b"     Running ";
+
+    log[at..]
+        .windows(marker.len())
+        .position(|w| w == marker)
+        .map_or(log.len(), |i| at + i)
+}
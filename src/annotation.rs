@@ -0,0 +1,344 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::io::{self, Write};
+
+use ansi::plain;
+use markdown::fence;
+use messages::{Diagnostic, JsonRun};
+use suite::{compile_error_location, Suite, Test, TestStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "lowercase"))]
+pub enum AnnotationLevel {
+    Error,
+    Warning,
+}
+
+impl AnnotationLevel {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            AnnotationLevel::Error => "error",
+            AnnotationLevel::Warning => "warning",
+        }
+    }
+}
+
+/// A failure or compiler diagnostic to show next to a source line.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Annotation {
+    pub level: AnnotationLevel,
+    pub title: String,
+    pub message: String,
+    pub file: Option<String>,
+    pub line: Option<u64>,
+    pub column: Option<u64>,
+    pub end_line: Option<u64>,
+    pub end_column: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnnotationFormat {
+    /// `::error file=src/lib.rs,line=12,col=9,title=...::message` workflow
+    /// commands.
+    GithubActions,
+    /// `##vso[task.logissue type=error;sourcepath=...]message` logging
+    /// commands.
+    AzurePipelines,
+    /// A Markdown body for `buildkite-agent annotate --style error`.
+    Buildkite,
+}
+
+fn verb(status: TestStatus) -> &'static str {
+    match status {
+        TestStatus::Timeout => "timed out",
+        TestStatus::Crashed => "crashed",
+        _ => "failed",
+    }
+}
+
+impl Annotation {
+    /// An error where `test` panicked; `None` unless it failed. Compile
+    /// errors read as tests take the location of their `-->` line.
+    pub fn from_test(suite: &Suite, test: &Test) -> Option<Annotation> {
+        if !test.status.is_failure() {
+            return None;
+        }
+
+        let error = test.error.as_ref().map_or("", |e| e.as_str());
+        let (title, message, location) = if test.is_compile_error() {
+            let message = error.lines().next().unwrap_or("");
+            ("compile error".to_string(), message, compile_error_location(error))
+        } else {
+            let title = format!("{} {}", suite.test_id(test), verb(test.status));
+            (title, test.panic_message().unwrap_or(""), test.panic_location())
+        };
+
+        Some(Annotation {
+            level: AnnotationLevel::Error,
+            title,
+            message: plain(message),
+            file: location.map(|l| l.file.to_string()),
+            line: location.map(|l| u64::from(l.line)),
+            column: location.and_then(|l| l.column).map(u64::from),
+            end_line: None,
+            end_column: None,
+        })
+    }
+
+    /// An annotation at the primary span of a compiler error or warning;
+    /// `None` for notes and help, and for summaries without spans such as
+    /// `aborting due to 2 previous errors`.
+    pub fn from_diagnostic(diagnostic: &Diagnostic) -> Option<Annotation> {
+        let level = match diagnostic.level.as_str() {
+            "warning" => AnnotationLevel::Warning,
+            level if level.starts_with("error") => AnnotationLevel::Error,
+            _ => return None,
+        };
+        let title = match diagnostic.code {
+            Some(ref code) => format!("{}[{}]", diagnostic.level, code),
+            None => diagnostic.level.clone(),
+        };
+        let span = diagnostic
+            .spans
+            .iter()
+            .find(|s| s.is_primary)
+            .or_else(|| diagnostic.spans.first())?;
+
+        Some(Annotation {
+            level,
+            title,
+            message: diagnostic.message.clone(),
+            file: Some(span.file_name.clone()),
+            line: Some(span.line_start),
+            column: Some(span.column_start),
+            end_line: Some(span.line_end),
+            end_column: Some(span.column_end),
+        })
+    }
+}
+
+/// An annotation for every failed test of `suites`, in log order.
+pub fn annotations(suites: &[Suite]) -> Vec<Annotation> {
+    suites
+        .iter()
+        .flat_map(|suite| suite.tests.iter().filter_map(move |test| Annotation::from_test(suite, test)))
+        .collect()
+}
+
+impl JsonRun {
+    /// Compiler errors and warnings at their spans, then failed tests. The
+    /// suites standing in for compile errors are left out, as the
+    /// diagnostics locate them better.
+    pub fn annotations(&self) -> Vec<Annotation> {
+        let mut annotations: Vec<Annotation> = self.diagnostics().filter_map(Annotation::from_diagnostic).collect();
+
+        annotations.extend(
+            self.suites
+                .iter()
+                .flat_map(|suite| suite.tests.iter().map(move |test| (suite, test)))
+                .filter(|&(_, test)| !test.is_compile_error())
+                .filter_map(|(suite, test)| Annotation::from_test(suite, test)),
+        );
+        annotations
+    }
+}
+
+fn github_data(text: &str) -> String {
+    text.replace('%', "%25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn github_property(text: &str) -> String {
+    github_data(text).replace(':', "%3A").replace(',', "%2C")
+}
+
+fn azure_data(text: &str) -> String {
+    text.replace('%', "%AZP25").replace('\r', "%0D").replace('\n', "%0A")
+}
+
+fn azure_property(text: &str) -> String {
+    azure_data(text).replace(';', "%3B").replace(']', "%5D")
+}
+
+fn write_github<W: Write>(output: &mut W, annotation: &Annotation) -> io::Result<()> {
+    let mut properties = vec![];
+    if let Some(ref file) = annotation.file {
+        properties.push(format!("file={}", github_property(file)));
+    }
+    let numbers = [
+        ("line", annotation.line),
+        ("col", annotation.column),
+        ("endLine", annotation.end_line),
+        ("endColumn", annotation.end_column),
+    ];
+    for &(key, value) in &numbers {
+        if let Some(value) = value {
+            properties.push(format!("{}={}", key, value));
+        }
+    }
+    properties.push(format!("title={}", github_property(&annotation.title)));
+
+    writeln!(
+        output,
+        "::{} {}::{}",
+        annotation.level.as_str(),
+        properties.join(","),
+        github_data(&annotation.message)
+    )
+}
+
+fn write_azure<W: Write>(output: &mut W, annotation: &Annotation) -> io::Result<()> {
+    let mut properties = vec![format!("type={}", annotation.level.as_str())];
+    if let Some(ref file) = annotation.file {
+        properties.push(format!("sourcepath={}", azure_property(file)));
+    }
+    if let Some(line) = annotation.line {
+        properties.push(format!("linenumber={}", line));
+    }
+    if let Some(column) = annotation.column {
+        properties.push(format!("columnnumber={}", column));
+    }
+
+    let message = if annotation.message.is_empty() {
+        annotation.title.clone()
+    } else {
+        format!("{}: {}", annotation.title, annotation.message)
+    };
+    writeln!(output, "##vso[task.logissue {}]{}", properties.join(";"), azure_data(&message))
+}
+
+fn write_buildkite<W: Write>(output: &mut W, annotation: &Annotation) -> io::Result<()> {
+    writeln!(output, "**{}**", annotation.title.replace('*', "\\*"))?;
+    if let (Some(file), Some(line)) = (annotation.file.as_ref(), annotation.line) {
+        match annotation.column {
+            Some(column) => writeln!(output, "`{}:{}:{}`", file, line, column)?,
+            None => writeln!(output, "`{}:{}`", file, line)?,
+        }
+    }
+    if !annotation.message.is_empty() {
+        let fence = fence(&annotation.message);
+        writeln!(output, "\n{}term\n{}\n{}", fence, annotation.message.trim_end(), fence)?;
+    }
+    writeln!(output)
+}
+
+/// Writes annotations in the form `format`'s CI provider turns into inline
+/// comments on source lines: printed to the job log for GitHub Actions and
+/// Azure Pipelines, and piped to `buildkite-agent annotate` for Buildkite.
+pub fn annotation_writer<W: Write>(
+    annotations: &[Annotation],
+    format: AnnotationFormat,
+    mut output: W,
+) -> io::Result<()> {
+    for annotation in annotations {
+        match format {
+            AnnotationFormat::GithubActions => write_github(&mut output, annotation)?,
+            AnnotationFormat::AzurePipelines => write_azure(&mut output, annotation)?,
+            AnnotationFormat::Buildkite => write_buildkite(&mut output, annotation)?,
+        }
+    }
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{annotation_writer, annotations, Annotation, AnnotationFormat, AnnotationLevel};
+    use fixtures::suites;
+    use messages::{Diagnostic, DiagnosticSpan};
+
+    fn written(format: AnnotationFormat) -> String {
+        let mut output = vec![];
+        annotation_writer(&annotations(&suites()), format, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn it_should_write_github_workflow_commands() {
+        assert_eq!(
+            written(AnnotationFormat::GithubActions),
+            "::error file=src/lib.rs,line=12,col=9,title=unit/app%3A%3Atests%3A%3Ait_fails failed::100%25 `a < b`%0A```
+::error title=unit/app%3A%3Atests%3A%3Ait_hangs timed out::
+::error file=src/main.rs,line=3,col=5,title=compile error::mismatched types
+"
+        );
+    }
+
+    #[test]
+    fn it_should_write_azure_logging_commands() {
+        assert_eq!(
+            written(AnnotationFormat::AzurePipelines),
+            "##vso[task.logissue type=error;sourcepath=src/lib.rs;linenumber=12;columnnumber=9]unit/app::tests::it_fails failed: 100%AZP25 `a < b`%0A```
+##vso[task.logissue type=error]unit/app::tests::it_hangs timed out
+##vso[task.logissue type=error;sourcepath=src/main.rs;linenumber=3;columnnumber=5]compile error: mismatched types
+"
+        );
+    }
+
+    #[test]
+    fn it_should_write_a_buildkite_annotation() {
+        assert_eq!(
+            written(AnnotationFormat::Buildkite),
+            "**unit/app::tests::it_fails failed**
+`src/lib.rs:12:9`
+
+````term
+100% `a < b`
+```
+````
+
+**unit/app::tests::it_hangs timed out**
+
+**compile error**
+`src/main.rs:3:5`
+
+```term
+mismatched types
+```
+
+"
+        );
+    }
+
+    #[test]
+    fn it_should_annotate_primary_spans_of_diagnostics() {
+        let span = |line, is_primary| DiagnosticSpan {
+            file_name: "src/lib.rs".to_string(),
+            line_start: line,
+            line_end: line,
+            column_start: 5,
+            column_end: 15,
+            is_primary,
+            label: None,
+        };
+        let diagnostic = Diagnostic {
+            level: "warning".to_string(),
+            message: "unused variable: `x`".to_string(),
+            code: Some("unused_variables".to_string()),
+            spans: vec![span(1, false), span(3, true)],
+            rendered: None,
+        };
+
+        let annotation = Annotation::from_diagnostic(&diagnostic).unwrap();
+
+        assert_eq!(annotation.level, AnnotationLevel::Warning);
+        assert_eq!(annotation.title, "warning[unused_variables]");
+        assert_eq!((annotation.line, annotation.end_column), (Some(3), Some(15)));
+        assert_eq!(
+            Annotation::from_diagnostic(&Diagnostic {
+                level: "note".to_string(),
+                ..diagnostic.clone()
+            }),
+            None
+        );
+        assert_eq!(
+            Annotation::from_diagnostic(&Diagnostic {
+                level: "error".to_string(),
+                message: "aborting due to 1 previous error".to_string(),
+                code: None,
+                spans: vec![],
+                rendered: None,
+            }),
+            None
+        );
+    }
+}
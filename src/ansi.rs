@@ -0,0 +1,198 @@
+use std::borrow::Cow;
+
+const ESC: u8 = 0x1b;
+const BEL: u8 = 0x07;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Colors {
+    Strip,
+    /// Keep color (SGR) sequences inside `---- name stdout ----` sections, so
+    /// `Test.error` still carries the colored panic and assertion output.
+    PreserveInFailures,
+}
+
+fn escape_len(input: &[u8]) -> usize {
+    match input.get(1) {
+        Some(&b'[') => input[2..]
+            .iter()
+            .position(|&c| (0x40..=0x7e).contains(&c))
+            .map_or(input.len(), |end| end + 3),
+        Some(&b']') => {
+            let mut i = 2;
+            while i < input.len() {
+                match input[i] {
+                    BEL => return i + 1,
+                    ESC if input.get(i + 1) == Some(&b'\\') => return i + 2,
+                    _ => i += 1,
+                }
+            }
+            input.len()
+        }
+        Some(&b'(') | Some(&b')') => 3.min(input.len()),
+        Some(_) => 2,
+        None => 1,
+    }
+}
+
+fn strip_escapes(line: &[u8], keep_colors: bool, output: &mut Vec<u8>) {
+    let mut i = 0;
+
+    while i < line.len() {
+        if line[i] == ESC {
+            let len = escape_len(&line[i..]);
+            let sequence = &line[i..i + len];
+
+            if keep_colors && sequence.len() > 2 && sequence[1] == b'[' && sequence.ends_with(b"m") {
+                output.extend_from_slice(sequence);
+            }
+            i += len;
+        } else {
+            output.push(line[i]);
+            i += 1;
+        }
+    }
+}
+
+fn is_failure_header(line: &[u8]) -> bool {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim();
+
+    line.starts_with("---- ") && line.ends_with(" ----")
+}
+
+fn ends_failure_section(line: &[u8]) -> bool {
+    let line = String::from_utf8_lossy(line);
+    let line = line.trim();
+
+    line == "failures:" || line.starts_with("test result: ")
+}
+
+/// Removes terminal escape sequences and progress-bar redraws from captured
+/// output, so logs produced with `CARGO_TERM_COLOR=always` (or a TTY) can be
+/// handed to `cargo_test_result_parser`.
+///
+/// Of a line redrawn with bare carriage returns only the final text is kept.
+/// `\r\n` line endings are left alone. Input without anything to strip is
+/// returned as is.
+pub fn strip_ansi<'a>(input: &'a [u8], colors: Colors) -> Cow<'a, [u8]> {
+    if !input.iter().any(|&c| c == ESC || c == b'\r') {
+        return Cow::Borrowed(input);
+    }
+
+    let mut output = Vec::with_capacity(input.len());
+    let mut in_failure = false;
+    let mut lines = input.split(|&c| c == b'\n').peekable();
+
+    while let Some(line) = lines.next() {
+        let (line, ending): (&[u8], &[u8]) = match lines.peek() {
+            Some(_) if line.ends_with(b"\r") => (&line[..line.len() - 1], b"\r\n"),
+            Some(_) => (line, b"\n"),
+            None => (line, b""),
+        };
+        let line = match line.iter().rposition(|&c| c == b'\r') {
+            Some(i) => &line[i + 1..],
+            None => line,
+        };
+
+        let start = output.len();
+        strip_escapes(line, false, &mut output);
+
+        if colors == Colors::PreserveInFailures {
+            if is_failure_header(&output[start..]) {
+                in_failure = true;
+            } else if ends_failure_section(&output[start..]) {
+                in_failure = false;
+            } else if in_failure {
+                output.truncate(start);
+                strip_escapes(line, true, &mut output);
+            }
+        }
+
+        output.extend_from_slice(ending);
+    }
+
+    Cow::Owned(output)
+}
+
+/// `text` without any escape sequences.
+pub fn plain(text: &str) -> String {
+    String::from_utf8_lossy(&strip_ansi(text.as_bytes(), Colors::Strip)).into_owned()
+}
+
+/// Spells out the characters `keep` rejects as in `\u{1b}[31m`, for
+/// report formats that cannot hold control characters.
+pub fn spell_out<F: Fn(char) -> bool>(text: &str, keep: F) -> Cow<'_, str> {
+    if text.chars().all(&keep) {
+        return Cow::Borrowed(text);
+    }
+
+    let mut escaped = String::with_capacity(text.len() + 16);
+    for c in text.chars() {
+        if keep(c) {
+            escaped.push(c);
+        } else {
+            escaped.extend(c.escape_unicode());
+        }
+    }
+    Cow::Owned(escaped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{strip_ansi, Colors};
+
+    #[test]
+    fn it_should_strip_colored_status_verbs() {
+        let output = &b"\x1b[1m\x1b[32m   Compiling\x1b[0m foo v0.1.0
+test it_works ... \x1b[32mok\x1b[0m
+"[..];
+
+        assert_eq!(
+            &strip_ansi(output, Colors::Strip)[..],
+            &b"   Compiling foo v0.1.0
+test it_works ... ok
+"[..]
+        );
+    }
+
+    #[test]
+    fn it_should_keep_the_last_progress_redraw() {
+        let output = &b"    Building [=====>    ] 10/20: foo\r\x1b[K    Building [=========>] 19/20: bar\r\x1b[K    Finished dev [unoptimized + debuginfo] target(s) in 1.2s\r\n"[..];
+
+        assert_eq!(
+            &strip_ansi(output, Colors::Strip)[..],
+            &b"    Finished dev [unoptimized + debuginfo] target(s) in 1.2s\r\n"[..]
+        );
+    }
+
+    #[test]
+    fn it_should_strip_osc_hyperlinks() {
+        let output = &b"\x1b]8;;file:///src/lib.rs\x07src/lib.rs\x1b]8;;\x1b\\:12"[..];
+
+        assert_eq!(&strip_ansi(output, Colors::Strip)[..], &b"src/lib.rs:12"[..]);
+    }
+
+    #[test]
+    fn it_should_preserve_colors_in_failure_text() {
+        let output = &b"failures:
+
+\x1b[1m---- fail stdout ----\x1b[0m
+thread 'fail' panicked at \x1b[31m'boom'\x1b[0m\x1b[K
+
+\x1b[1mfailures:\x1b[0m
+    fail
+"[..];
+
+        assert_eq!(
+            &strip_ansi(output, Colors::PreserveInFailures)[..],
+            &b"failures:
+
+---- fail stdout ----
+thread 'fail' panicked at \x1b[31m'boom'\x1b[0m
+
+failures:
+    fail
+"[..]
+        );
+    }
+}
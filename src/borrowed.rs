@@ -0,0 +1,158 @@
+use std::borrow::Cow;
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use parse::{parse_suites, ParseError};
+use suite::{self, SuiteKind, SuiteState, TestStatus};
+
+/// A `Test` whose text still points into the log it was parsed from. Only
+/// text that had to be rewritten, such as CRLF failure output or invalid
+/// UTF-8, is owned.
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Test<'a> {
+    pub name: Cow<'a, str>,
+    pub status: TestStatus,
+    pub error: Option<Cow<'a, str>>,
+    pub stdout: Option<Cow<'a, str>>,
+    pub lossy: bool,
+    pub duration: Option<f64>,
+}
+
+/// A `Suite` borrowing from the log it was parsed from, see `Test`.
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Suite<'a> {
+    pub name: Cow<'a, str>,
+    pub state: SuiteState,
+    pub passed: i64,
+    pub failed: i64,
+    pub ignored: i64,
+    pub measured: i64,
+    pub total: i64,
+    pub tests: Vec<Test<'a>>,
+    pub anonymous_tests: bool,
+    pub lossy: bool,
+    pub package: Option<Cow<'a, str>>,
+    pub crate_name: Option<Cow<'a, str>>,
+    pub kind: SuiteKind,
+    pub source: Option<Cow<'a, str>>,
+    pub binary: Option<Cow<'a, str>>,
+    pub duration: Option<f64>,
+    pub unmatched_failures: Vec<Cow<'a, str>>,
+}
+
+fn owned(text: Option<Cow<str>>) -> Option<String> {
+    text.map(Cow::into_owned)
+}
+
+impl<'a> Test<'a> {
+    pub fn into_owned(self) -> suite::Test {
+        suite::Test {
+            name: self.name.into_owned(),
+            status: self.status,
+            error: owned(self.error),
+            stdout: owned(self.stdout),
+            lossy: self.lossy,
+            duration: self.duration,
+        }
+    }
+}
+
+impl<'a> Suite<'a> {
+    pub fn into_owned(self) -> suite::Suite {
+        suite::Suite {
+            name: self.name.into_owned(),
+            state: self.state,
+            passed: self.passed,
+            failed: self.failed,
+            ignored: self.ignored,
+            measured: self.measured,
+            total: self.total,
+            tests: self.tests.into_iter().map(Test::into_owned).collect(),
+            anonymous_tests: self.anonymous_tests,
+            lossy: self.lossy,
+            package: owned(self.package),
+            crate_name: owned(self.crate_name),
+            kind: self.kind,
+            source: owned(self.source),
+            binary: owned(self.binary),
+            duration: self.duration,
+            unmatched_failures: self.unmatched_failures.into_iter().map(Cow::into_owned).collect(),
+        }
+    }
+}
+
+/// Like `cargo_results::parse`, but the suites borrow from `input` instead
+/// of copying every name and failure message out of it.
+pub fn parse(input: &[u8]) -> Result<Vec<Suite<'_>>, ParseError> {
+    parse_suites(input)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::borrow::Cow;
+
+    use super::parse;
+    use suite::TestStatus;
+
+    #[test]
+    fn it_should_borrow_from_the_input() {
+        let output = b"     Running tests/api.rs (target/debug/deps/api-0b1c2d3e4f5a6b7c)
+
+running 2 tests
+test it_fails ... FAILED
+test it_works ... ok
+
+failures:
+
+---- it_fails stdout ----
+thread 'it_fails' panicked at tests/api.rs:4:5
+
+failures:
+    it_fails
+
+test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+
+        let suites = parse(output).unwrap();
+        let suite = &suites[0];
+        let test = &suite.tests[0];
+
+        assert!(matches!(suite.name, Cow::Borrowed(_)));
+        assert!(matches!(suite.source, Some(Cow::Borrowed("tests/api.rs"))));
+        assert!(matches!(test.name, Cow::Borrowed("it_fails")));
+        assert!(matches!(
+            test.error,
+            Some(Cow::Borrowed("thread 'it_fails' panicked at tests/api.rs:4:5"))
+        ));
+        assert_eq!(suite.tests[1].status, TestStatus::Passed);
+        assert_eq!(suite.tests[1].error, None);
+    }
+
+    #[test]
+    fn it_should_own_rewritten_text() {
+        let output = b"     Running target/debug/deps/app-622eefdc86aa5319\r
+\r
+running 1 test\r
+test it_fails ... FAILED\r
+\r
+failures:\r
+\r
+---- it_fails stdout ----\r
+left: 1\r
+right: 2\r
+\r
+failures:\r
+    it_fails\r
+\r
+test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out\r
+";
+
+        let suite = parse(output).unwrap().remove(0).into_owned();
+
+        assert_eq!(suite.tests[0].error, Some("left: 1\nright: 2".to_string()));
+        assert!(!suite.lossy);
+        assert!(!suite.tests[0].lossy);
+    }
+}
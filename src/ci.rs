@@ -0,0 +1,455 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use suite::split_status;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CiPrefix {
+    /// `2026-01-01T12:00:00.1234567Z ` in front of every line of a downloaded
+    /// GitHub Actions log, `##[group]` and other `##[...]` markers, and the
+    /// `Run` group a step's log starts with.
+    GithubActions,
+    /// `section_start:<time>:<name>` and `section_end:` markers, and the
+    /// `<time> 00O ` prefix runners add with `FF_TIMESTAMPS` enabled. Of a
+    /// log with sections only the script's are kept, without the `$ cargo
+    /// test` lines echoing its commands.
+    Gitlab,
+    /// `[Pipeline]` step lines and the `[2026-01-01T12:00:00.123Z] ` prefix
+    /// of the timestamper plugin, and the `+ cargo test` and `Running on`
+    /// lines following a step.
+    Jenkins,
+    /// Any other text to remove from the start of lines.
+    Literal(String),
+}
+
+/// A log with its CI prefixes removed. `timestamps` holds the time, in
+/// seconds since the Unix epoch, each line of `text` was written at, where the
+/// CI provider recorded one.
+#[derive(Debug, Default, PartialEq)]
+pub struct CiLog {
+    pub text: Vec<u8>,
+    pub timestamps: Vec<Option<f64>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Timing {
+    pub name: String,
+    pub seconds: f64,
+}
+
+fn number(text: &[u8]) -> Option<i64> {
+    if text.is_empty() || !text.iter().all(u8::is_ascii_digit) {
+        return None;
+    }
+
+    text.iter().try_fold(0i64, |n, &c| n.checked_mul(10)?.checked_add(i64::from(c - b'0')))
+}
+
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = (if year >= 0 { year } else { year - 399 }) / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Reads an RFC 3339 time in UTC, `2026-01-01T12:00:00.1234567Z`, returning
+/// the seconds since the Unix epoch and the length of the text.
+fn timestamp(line: &[u8]) -> Option<(f64, usize)> {
+    if line.len() < 20 || line[4] != b'-' || line[7] != b'-' || line[10] != b'T' {
+        return None;
+    }
+    if line[13] != b':' || line[16] != b':' {
+        return None;
+    }
+
+    let days = days_from_civil(number(&line[0..4])?, number(&line[5..7])?, number(&line[8..10])?);
+    let seconds = number(&line[11..13])? * 3600 + number(&line[14..16])? * 60 + number(&line[17..19])?;
+    let mut seconds = (days * 86_400 + seconds) as f64;
+    let mut end = 19;
+
+    if line[end] == b'.' {
+        let digits = line[end + 1..].iter().take_while(|c| c.is_ascii_digit()).count();
+        let fraction = &line[end + 1..end + 1 + digits];
+
+        seconds += number(fraction)? as f64 / 10f64.powi(digits as i32);
+        end += 1 + digits;
+    }
+
+    match line.get(end) {
+        Some(&b'Z') => Some((seconds, end + 1)),
+        _ => None,
+    }
+}
+
+fn strip_timestamp(line: &[u8]) -> Option<(f64, &[u8])> {
+    let (seconds, len) = timestamp(line)?;
+
+    match line.get(len) {
+        Some(&b' ') => Some((seconds, &line[len + 1..])),
+        None => Some((seconds, &line[len..])),
+        _ => None,
+    }
+}
+
+/// `00O ` or `01E+ `: the stream a GitLab line came from, after its time.
+fn strip_gitlab_stream(line: &[u8]) -> &[u8] {
+    let stream_len = match line {
+        [a, b, b'O' | b'E', b'+', b' ', ..] if a.is_ascii_hexdigit() && b.is_ascii_hexdigit() => 5,
+        [a, b, b'O' | b'E', b' ', ..] if a.is_ascii_hexdigit() && b.is_ascii_hexdigit() => 4,
+        _ => 0,
+    };
+
+    &line[stream_len..]
+}
+
+/// `section_start:1700000000:name[collapsed=true]\r\x1b[0K`, returning its
+/// time and whether it starts a script section. The section header follows.
+fn gitlab_section(line: &[u8]) -> Option<(f64, Option<bool>)> {
+    let (rest, start) = match line.strip_prefix(b"section_start:") {
+        Some(rest) => (rest, true),
+        None => (line.strip_prefix(b"section_end:")?, false),
+    };
+    let colon = rest.iter().position(|&c| c == b':')?;
+    let seconds = number(&rest[..colon])? as f64;
+    let name = &rest[colon + 1..];
+    let script = name.starts_with(b"step_script") || name.starts_with(b"build_script");
+
+    Some((seconds, if start { Some(script) } else { None }))
+}
+
+/// `$ cargo test`, as GitLab echoes script commands, in bold green.
+fn is_gitlab_command(line: &[u8]) -> bool {
+    line.starts_with(b"$ ") || line.starts_with(b"\x1b[32;1m$ ")
+}
+
+/// What `strip_line` carries from one line to the next.
+#[derive(Default)]
+struct State {
+    /// Inside the `##[group]Run ...` header of a GitHub Actions step.
+    run_group: bool,
+    /// Inside a GitLab script section; `None` when the log has none.
+    script: Option<bool>,
+    /// Right after a Jenkins `[Pipeline]` line.
+    pipeline: bool,
+}
+
+fn strip_jenkins_timestamp(line: &[u8]) -> Option<(f64, &[u8])> {
+    let (seconds, len) = timestamp(line.strip_prefix(b"[")?)?;
+
+    match line[len + 1..].strip_prefix(b"] ") {
+        Some(rest) => Some((seconds, rest)),
+        None => line[len + 1..].strip_prefix(b"]").map(|rest| (seconds, rest)),
+    }
+}
+
+/// Strips one line. `None` means the whole line is CI noise.
+fn strip_line<'a>(
+    mut line: &'a [u8],
+    prefixes: &[CiPrefix],
+    state: &mut State,
+    time: &mut Option<f64>,
+) -> Option<&'a [u8]> {
+    for prefix in prefixes {
+        match *prefix {
+            CiPrefix::GithubActions => {
+                if let Some((seconds, rest)) = strip_timestamp(line) {
+                    *time = Some(seconds);
+                    line = rest;
+                }
+                if let Some(marker) = line.strip_prefix(b"##[") {
+                    if marker.starts_with(b"group]Run ") {
+                        state.run_group = true;
+                    } else if marker.starts_with(b"endgroup]") {
+                        state.run_group = false;
+                    }
+                    return None;
+                }
+                if state.run_group {
+                    return None;
+                }
+            }
+            CiPrefix::Gitlab => {
+                if let Some((seconds, rest)) = strip_timestamp(line) {
+                    *time = Some(seconds);
+                    line = strip_gitlab_stream(rest);
+                }
+                if let Some((seconds, script)) = gitlab_section(line) {
+                    time.get_or_insert(seconds);
+                    state.script = Some(script.unwrap_or(false));
+                    return None;
+                }
+                if state.script == Some(false) || is_gitlab_command(line) {
+                    return None;
+                }
+            }
+            CiPrefix::Jenkins => {
+                if let Some((seconds, rest)) = strip_jenkins_timestamp(line) {
+                    *time = Some(seconds);
+                    line = rest;
+                }
+                if line.starts_with(b"[Pipeline]") {
+                    state.pipeline = true;
+                    return None;
+                }
+                if state.pipeline && (line.starts_with(b"+ ") || line.starts_with(b"Running on ")) {
+                    return None;
+                }
+                state.pipeline = false;
+            }
+            CiPrefix::Literal(ref text) => {
+                line = line.strip_prefix(text.as_bytes()).unwrap_or(line);
+            }
+        }
+    }
+
+    Some(line)
+}
+
+/// Removes the prefixes and lines CI providers add to a job log, so it can
+/// be handed to `cargo_test_result_parser`. Run it before `strip_ansi`, as
+/// GitLab section markers use carriage returns.
+pub fn strip_ci_prefixes(input: &[u8], prefixes: &[CiPrefix]) -> CiLog {
+    let mut log = CiLog {
+        text: Vec::with_capacity(input.len()),
+        timestamps: vec![],
+    };
+    let mut state = State::default();
+    if prefixes.contains(&CiPrefix::Gitlab) && input.windows(14).any(|w| w == b"section_start:") {
+        state.script = Some(false);
+    }
+    let mut lines = input.split(|&c| c == b'\n').peekable();
+
+    while let Some(line) = lines.next() {
+        let ending: &[u8] = if lines.peek().is_some() { b"\n" } else { b"" };
+        let mut time = None;
+
+        if let Some(line) = strip_line(line, prefixes, &mut state, &mut time) {
+            if line.is_empty() && ending.is_empty() {
+                break;
+            }
+            log.text.extend_from_slice(line);
+            log.text.extend_from_slice(ending);
+            log.timestamps.push(time);
+        }
+    }
+
+    log
+}
+
+fn elapsed(start: Option<f64>, end: Option<f64>) -> Option<f64> {
+    Some((end? - start?).max(0.0))
+}
+
+impl CiLog {
+    fn lines(&self) -> impl Iterator<Item = (String, Option<f64>)> + '_ {
+        self.text
+            .split(|&c| c == b'\n')
+            .map(|l| String::from_utf8_lossy(l).trim().to_string())
+            .zip(self.timestamps.iter().cloned())
+    }
+
+    /// Estimates how long each suite ran, from the time of its `Running` or
+    /// `Doc-tests` line to that of its `test result:` line. Suites are named
+    /// as in `Suite.name`.
+    pub fn suite_timings(&self) -> Vec<Timing> {
+        let mut timings = vec![];
+        let mut current: Option<(String, Option<f64>)> = None;
+
+        for (line, time) in self.lines() {
+            let name = line
+                .strip_prefix("Running ")
+                .or_else(|| line.strip_prefix("Doc-tests "));
+
+            if let Some(name) = name {
+                current = Some((name.trim().to_string(), time));
+            } else if line.starts_with("test result: ") {
+                if let Some((name, start)) = current.take() {
+                    if let Some(seconds) = elapsed(start, time) {
+                        timings.push(Timing { name, seconds });
+                    }
+                }
+            }
+        }
+
+        timings
+    }
+
+    /// Estimates how long each test ran, from the time of the previous
+    /// result (or of `running N tests`) to that of its own result. libtest
+    /// runs tests in parallel, so this is only an upper bound.
+    pub fn test_timings(&self) -> Vec<Timing> {
+        let mut timings = vec![];
+        let mut previous = None;
+
+        for (line, time) in self.lines() {
+            if line.starts_with("running ") {
+                previous = time;
+            } else if let Some((name, _)) = line.strip_prefix("test ").and_then(split_status) {
+                if let Some(seconds) = elapsed(previous, time) {
+                    timings.push(Timing {
+                        name: name.to_string(),
+                        seconds,
+                    });
+                }
+                previous = time;
+            }
+        }
+
+        timings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{strip_ci_prefixes, timestamp, CiPrefix};
+    use parse;
+    use suite::Suite;
+
+    #[test]
+    fn it_should_read_rfc3339_timestamps() {
+        assert_eq!(timestamp(b"1970-01-01T00:00:00Z"), Some((0.0, 20)));
+        assert_eq!(timestamp(b"2026-01-01T12:00:00.5Z test"), Some((1_767_268_800.5, 22)));
+        assert_eq!(timestamp(b"running 2 tests"), None);
+    }
+
+    #[test]
+    fn it_should_strip_github_actions_timestamps() {
+        let log = strip_ci_prefixes(
+            b"2026-01-01T12:00:00.1000000Z running 1 test\n2026-01-01T12:00:01.6000000Z test it_works ... ok\n",
+            &[CiPrefix::GithubActions],
+        );
+
+        assert_eq!(&log.text[..], &b"running 1 test\ntest it_works ... ok\n"[..]);
+        assert_eq!(
+            log.timestamps,
+            vec![Some(1_767_268_800.1), Some(1_767_268_801.6)]
+        );
+    }
+
+    #[test]
+    fn it_should_keep_only_gitlab_script_sections() {
+        let log = strip_ci_prefixes(
+            b"Running with gitlab-runner 16.5.0\n\
+              section_start:1767268800:step_script\r\x1b[0KExecuting \"step_script\" stage\n\
+              \x1b[32;1m$ cargo test\x1b[0;m\n\
+              running 0 tests\n\
+              section_end:1767268805:step_script\r\x1b[0K\n\
+              Job succeeded\n",
+            &[CiPrefix::Gitlab],
+        );
+
+        assert_eq!(&log.text[..], &b"running 0 tests\n"[..]);
+        assert_eq!(log.timestamps, vec![None]);
+    }
+
+    #[test]
+    fn it_should_drop_jenkins_pipeline_lines() {
+        let log = strip_ci_prefixes(
+            b"[Pipeline] sh\n[2026-01-01T12:00:00.000Z] + cargo test\n[2026-01-01T12:00:02.000Z] running 0 tests\n",
+            &[CiPrefix::Jenkins],
+        );
+
+        assert_eq!(&log.text[..], &b"running 0 tests\n"[..]);
+        assert_eq!(log.timestamps, vec![Some(1_767_268_802.0)]);
+    }
+
+    fn parsed(log: &[u8], prefix: CiPrefix) -> Vec<Suite> {
+        parse(&strip_ci_prefixes(log, &[prefix]).text).unwrap().suites
+    }
+
+    #[test]
+    fn it_should_parse_a_github_actions_step_log() {
+        let suites = parsed(
+            b"2026-01-01T12:00:00.0000000Z ##[group]Run cargo test
+2026-01-01T12:00:00.0000000Z cargo test
+2026-01-01T12:00:00.0000000Z shell: /usr/bin/bash -e {0}
+2026-01-01T12:00:00.0000000Z env:
+2026-01-01T12:00:00.0000000Z   CARGO_TERM_COLOR: never
+2026-01-01T12:00:00.0000000Z ##[endgroup]
+2026-01-01T12:00:00.5000000Z    Compiling app v0.1.0 (/home/runner/work/app/app)
+2026-01-01T12:00:04.0000000Z     Finished `test` profile [unoptimized + debuginfo] target(s) in 4.00s
+2026-01-01T12:00:04.5000000Z      Running unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+2026-01-01T12:00:04.5000000Z 
+2026-01-01T12:00:04.5000000Z running 1 test
+2026-01-01T12:00:05.0000000Z test tests::it_works ... ok
+2026-01-01T12:00:05.0000000Z 
+2026-01-01T12:00:05.0000000Z test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.50s
+2026-01-01T12:00:05.0000000Z 
+",
+            CiPrefix::GithubActions,
+        );
+
+        assert_eq!(suites.len(), 1);
+        assert_eq!(suites[0].passed, 1);
+    }
+
+    #[test]
+    fn it_should_parse_a_gitlab_job_log() {
+        let suites = parsed(
+            b"Running with gitlab-runner 16.5.0 (853330f9)
+  on docker-runner abc123, system ID: s_0123456789ab
+section_start:1767268800:prepare_executor\r\x1b[0K\x1b[0K\x1b[36;1mPreparing the \"docker\" executor\x1b[0;m
+Using Docker executor with image rust:1.80 ...
+section_end:1767268801:prepare_executor\r\x1b[0K
+section_start:1767268801:get_sources\r\x1b[0K\x1b[0K\x1b[36;1mGetting source from Git repository\x1b[0;m
+Fetching changes with git depth set to 20...
+section_end:1767268802:get_sources\r\x1b[0K
+section_start:1767268802:step_script\r\x1b[0K\x1b[0K\x1b[36;1mExecuting \"step_script\" stage of the job script\x1b[0;m
+\x1b[32;1m$ cargo test\x1b[0;m
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.05s
+     Running unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+
+running 1 test
+test tests::it_works ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+section_end:1767268805:step_script\r\x1b[0K
+section_start:1767268805:cleanup_file_variables\r\x1b[0K\x1b[0K\x1b[36;1mCleaning up project directory and file based variables\x1b[0;m
+section_end:1767268806:cleanup_file_variables\r\x1b[0K
+\x1b[32;1mJob succeeded\x1b[0;m
+",
+            CiPrefix::Gitlab,
+        );
+
+        assert_eq!(suites.len(), 1);
+        assert_eq!(suites[0].passed, 1);
+    }
+
+    #[test]
+    fn it_should_parse_a_jenkins_pipeline_log() {
+        let suites = parsed(
+            b"[Pipeline] Start of Pipeline
+[Pipeline] node
+Running on Jenkins in /var/jenkins_home/workspace/app
+[Pipeline] {
+[Pipeline] stage
+[Pipeline] { (Test)
+[Pipeline] sh
+[2026-01-01T12:00:00.000Z] + cargo test
+[2026-01-01T12:00:02.000Z]     Finished `test` profile [unoptimized + debuginfo] target(s) in 0.05s
+[2026-01-01T12:00:02.000Z]      Running unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+[2026-01-01T12:00:02.000Z] 
+[2026-01-01T12:00:02.000Z] running 1 test
+[2026-01-01T12:00:02.500Z] test tests::it_works ... ok
+[2026-01-01T12:00:02.500Z] 
+[2026-01-01T12:00:02.500Z] test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.50s
+[2026-01-01T12:00:02.500Z] 
+[Pipeline] }
+[Pipeline] // stage
+[Pipeline] }
+[Pipeline] // node
+[Pipeline] End of Pipeline
+Finished: SUCCESS
+",
+            CiPrefix::Jenkins,
+        );
+
+        assert_eq!(suites.len(), 1);
+        assert_eq!(suites[0].passed, 1);
+    }
+}
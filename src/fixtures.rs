@@ -0,0 +1,71 @@
+use suite::{Suite, SuiteKind, SuiteState, Test, TestStatus, COMPILE_FAILED};
+
+/// The run every report writer is tested against: a unit suite with each
+/// kind of outcome, a doc-test suite and a compile error.
+pub fn suites() -> Vec<Suite> {
+    vec![
+        Suite {
+            name: "unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)".to_string(),
+            crate_name: Some("app".to_string()),
+            kind: SuiteKind::Unit,
+            state: SuiteState::Failed,
+            passed: 1,
+            failed: 2,
+            ignored: 1,
+            total: 4,
+            duration: Some(1.5),
+            tests: vec![
+                Test {
+                    name: "tests::it_works".to_string(),
+                    duration: Some(0.25),
+                    ..Default::default()
+                },
+                Test {
+                    name: "tests::it_fails".to_string(),
+                    status: TestStatus::Failed,
+                    error: Some(
+                        "thread 'tests::it_fails' panicked at src/lib.rs:12:9:\n\x1b[31m100% `a < b`\x1b[0m\n```"
+                            .to_string(),
+                    ),
+                    ..Default::default()
+                },
+                Test {
+                    name: "tests::it_hangs".to_string(),
+                    status: TestStatus::Timeout,
+                    ..Default::default()
+                },
+                Test {
+                    name: "tests::it_is_slow".to_string(),
+                    status: TestStatus::Ignored,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        },
+        Suite {
+            name: "app".to_string(),
+            crate_name: Some("app".to_string()),
+            kind: SuiteKind::Doc,
+            passed: 1,
+            total: 1,
+            tests: vec![Test {
+                name: "src/lib.rs - add (line 3)".to_string(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+        Suite {
+            name: "unknown".to_string(),
+            state: SuiteState::Failed,
+            failed: 1,
+            total: 1,
+            tests: vec![Test {
+                name: COMPILE_FAILED.to_string(),
+                status: TestStatus::Failed,
+                error: Some("mismatched types\n --> src/main.rs:3:5\n  |".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+    ]
+}
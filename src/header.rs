@@ -5,9 +5,21 @@ named!(
     do_parse!(ws!(tag!("Blocking")) >> rest_of_line >> ())
 );
 
+/// `Compiling foo v0.1.0 (/work/foo)` names a local package; registry
+/// dependencies are printed without a source.
+fn local_package(line: &str) -> Option<String> {
+    let mut words = line.split_whitespace();
+    let name = words.next()?;
+
+    match (words.next(), words.next()) {
+        (Some(_), Some(source)) if source.starts_with('(') => Some(name.to_string()),
+        _ => None,
+    }
+}
+
 named!(
-    compiling<()>,
-    do_parse!(ws!(tag!("Compiling")) >> rest_of_line >> ())
+    compiling<&[u8], Option<String> >,
+    do_parse!(ws!(tag!("Compiling")) >> line: rest_of_line >> (local_package(&line)))
 );
 
 named!(
@@ -36,12 +48,18 @@ named!(
 );
 
 named!(
-    pub cargo_header<()>,
-    do_parse!(
-        many0!(
-            alt!(blocking | updating | downloading | downloaded | installing | compiling | finished)
-        ) >>
-        ()
+    pub header_line<()>,
+    alt!(
+        blocking | updating | downloading | downloaded | installing | map!(compiling, |_| ()) |
+        finished
+    )
+);
+
+named!(
+    pub cargo_header<Vec<String> >,
+    map!(
+        many0!(alt!(compiling | map!(header_line, |_| None))),
+        |packages: Vec<Option<String>>| packages.into_iter().flatten().collect()
     )
 );
 
@@ -93,7 +111,9 @@ mod tests {
         let output = &b"   Compiling docker-command v0.1.0 (file:///Users/joegrund/projects/docker-command-rs)
 "[..];
 
-        assert_done(compiling(output), ());
+        assert_done(compiling(output), Some("docker-command".to_string()));
+        assert_done(compiling(&b"   Compiling libc v0.2.33
+"[..]), None);
     }
 
     #[test]
@@ -202,6 +222,6 @@ mod tests {
     Finished dev [unoptimized + debuginfo] target(s) in 862.1 secs
 "[..];
 
-        assert_done(cargo_header(output), ());
+        assert_done(cargo_header(output), vec!["libzfs-sys".to_string()]);
     }
 }
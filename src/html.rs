@@ -0,0 +1,312 @@
+use std::io::{self, Write};
+
+use ansi::{plain, spell_out};
+use query::Query;
+use suite::{ModuleNode, Suite, Test, TestStatus};
+
+const STYLE: &str = r#"
+body { font: 14px/1.4 system-ui, sans-serif; margin: 2em; color: #1f2328; background: #fff; }
+h1 { font-size: 1.5em; margin: 0 0 .25em; }
+table { border-collapse: collapse; width: 100%; margin: .5em 0; }
+th, td { text-align: left; padding: .2em .6em; border-bottom: 1px solid #d0d7de; vertical-align: top; }
+th { cursor: pointer; user-select: none; white-space: nowrap; }
+th[aria-sort=ascending]::after { content: " \25B2"; }
+th[aria-sort=descending]::after { content: " \25BC"; }
+td.number, th.number { text-align: right; }
+details.group { margin: .25em 0 .25em 1em; }
+details.suite { margin-left: 0; border-top: 1px solid #d0d7de; padding-top: .25em; }
+summary { cursor: pointer; }
+.controls { display: flex; gap: 1em; flex-wrap: wrap; align-items: center; margin: 1em 0; }
+.controls input[type=search] { padding: .3em .5em; min-width: 20em; }
+.status { display: inline-block; min-width: 4.5em; font-weight: 600; }
+.pass, .bench { color: #1a7f37; }
+.fail, .timeout, .crashed { color: #cf222e; }
+.ignored { color: #9a6700; }
+.counts, .time { color: #656d76; margin-left: .5em; }
+pre { background: #f6f8fa; padding: .5em; overflow-x: auto; white-space: pre-wrap; margin: .25em 0; }
+@media (prefers-color-scheme: dark) {
+  body { color: #e6edf3; background: #0d1117; }
+  th, td, details.suite { border-color: #30363d; }
+  pre { background: #161b22; }
+}
+"#;
+
+const SCRIPT: &str = r#"
+(function () {
+  var search = document.getElementById("search");
+  var filters = document.querySelectorAll(".status-filter");
+
+  function apply() {
+    var query = search.value.toLowerCase();
+    var shown = {};
+    filters.forEach(function (filter) { shown[filter.value] = filter.checked; });
+    document.querySelectorAll("tr.test").forEach(function (row) {
+      row.hidden = !shown[row.dataset.status] || row.dataset.name.toLowerCase().indexOf(query) === -1;
+    });
+    document.querySelectorAll("details.group").forEach(function (group) {
+      if (group.querySelector("tr.test")) {
+        group.hidden = !group.querySelector("tr.test:not([hidden])");
+        if (query && !group.hidden) { group.open = true; }
+      }
+    });
+  }
+
+  function value(row, index, numeric) {
+    var text = row.cells[index].dataset.value;
+    if (!numeric) { return text; }
+    var number = parseFloat(text);
+    return isNaN(number) ? -1 : number;
+  }
+
+  document.querySelectorAll("table.sortable th").forEach(function (th) {
+    th.addEventListener("click", function () {
+      var table = th.closest("table");
+      var numeric = th.classList.contains("number");
+      var ascending = th.getAttribute("aria-sort") !== "ascending";
+      var body = table.tBodies[0];
+      var rows = Array.prototype.slice.call(body.rows);
+
+      table.querySelectorAll("th").forEach(function (other) { other.removeAttribute("aria-sort"); });
+      th.setAttribute("aria-sort", ascending ? "ascending" : "descending");
+      rows.sort(function (a, b) {
+        var x = value(a, th.cellIndex, numeric);
+        var y = value(b, th.cellIndex, numeric);
+        var order = numeric ? x - y : x.localeCompare(y);
+        return ascending ? order : -order;
+      });
+      rows.forEach(function (row) { body.appendChild(row); });
+    });
+  });
+
+  search.addEventListener("input", apply);
+  filters.forEach(function (filter) { filter.addEventListener("change", apply); });
+})();
+"#;
+
+const STATUSES: [TestStatus; 6] = [
+    TestStatus::Passed,
+    TestStatus::Failed,
+    TestStatus::Ignored,
+    TestStatus::Bench,
+    TestStatus::Timeout,
+    TestStatus::Crashed,
+];
+
+/// Escapes markup, and spells out control characters HTML cannot hold.
+fn escape(text: &str) -> String {
+    spell_out(text, |c| c == '\t' || c == '\n' || !c.is_control())
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn seconds(duration: Option<f64>) -> String {
+    duration.map_or_else(String::new, |d| format!("{:.3}s", d))
+}
+
+fn duration_value(duration: Option<f64>) -> String {
+    duration.map_or_else(String::new, |d| d.to_string())
+}
+
+fn counts(passed: i64, failed: i64, ignored: i64) -> String {
+    format!("{} passed, {} failed, {} ignored", passed, failed, ignored)
+}
+
+fn write_test<W: Write>(output: &mut W, node: &ModuleNode, test: &Test) -> io::Result<()> {
+    let name = match node.path {
+        "" => test.name.as_str(),
+        path => test.name[path.len()..].trim_start_matches("::"),
+    };
+    let status = test.status.as_str();
+
+    writeln!(output, "<tr class=\"test\" data-status=\"{}\" data-name=\"{}\">", status, escape(&test.name))?;
+    write!(output, "<td data-value=\"{}\">", escape(name))?;
+    if test.error.is_some() || test.stdout.is_some() {
+        write!(output, "<details{}><summary>{}</summary>", if test.status.is_failure() { " open" } else { "" }, escape(name))?;
+        if let Some(ref error) = test.error {
+            write!(output, "<pre class=\"output\">{}</pre>", escape(&plain(error)))?;
+        }
+        if let Some(ref stdout) = test.stdout {
+            write!(output, "<div>stdout</div><pre class=\"stdout\">{}</pre>", escape(&plain(stdout)))?;
+        }
+        write!(output, "</details>")?;
+    } else {
+        write!(output, "{}", escape(name))?;
+    }
+    writeln!(output, "</td>")?;
+    writeln!(output, "<td data-value=\"{0}\"><span class=\"status {0}\">{0}</span></td>", status)?;
+    writeln!(
+        output,
+        "<td class=\"number\" data-value=\"{}\">{}</td>",
+        duration_value(test.duration),
+        seconds(test.duration)
+    )?;
+    writeln!(output, "</tr>")
+}
+
+fn write_tests<W: Write>(output: &mut W, node: &ModuleNode) -> io::Result<()> {
+    if node.tests.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(output, "<table class=\"sortable\">")?;
+    writeln!(output, "<thead><tr><th>Test</th><th>Status</th><th class=\"number\">Time</th></tr></thead>")?;
+    writeln!(output, "<tbody>")?;
+    for test in &node.tests {
+        write_test(output, node, test)?;
+    }
+    writeln!(output, "</tbody>\n</table>")
+}
+
+fn write_module<W: Write>(output: &mut W, node: &ModuleNode) -> io::Result<()> {
+    writeln!(output, "<details class=\"group module\"{}>", if node.failed > 0 { " open" } else { "" })?;
+    writeln!(
+        output,
+        "<summary><code>{}</code><span class=\"counts\">{}</span><span class=\"time\">{}</span></summary>",
+        escape(node.name),
+        counts(node.passed, node.failed, node.ignored),
+        seconds(node.duration)
+    )?;
+    write_tests(output, node)?;
+    for module in &node.modules {
+        write_module(output, module)?;
+    }
+    writeln!(output, "</details>")
+}
+
+fn write_suite<W: Write>(output: &mut W, suite: &Suite) -> io::Result<()> {
+    let tree = suite.module_tree();
+    let state = suite.state.as_str();
+
+    writeln!(output, "<details class=\"group suite\"{}>", if suite.failed > 0 { " open" } else { "" })?;
+    writeln!(
+        output,
+        "<summary><span class=\"status {0}\">{0}</span><code>{1}</code><span class=\"counts\">{2}</span><span class=\"time\">{3}</span></summary>",
+        state,
+        escape(&suite.name),
+        counts(suite.passed, suite.failed, suite.ignored),
+        seconds(suite.duration)
+    )?;
+    write_tests(output, &tree)?;
+    for module in &tree.modules {
+        write_module(output, module)?;
+    }
+    writeln!(output, "</details>")
+}
+
+fn write_summary<W: Write>(output: &mut W, suites: &[Suite]) -> io::Result<()> {
+    writeln!(output, "<table class=\"sortable suites\">")?;
+    writeln!(
+        output,
+        "<thead><tr><th>Suite</th><th>Status</th><th class=\"number\">Passed</th><th class=\"number\">Failed</th>\
+         <th class=\"number\">Ignored</th><th class=\"number\">Time</th></tr></thead>"
+    )?;
+    writeln!(output, "<tbody>")?;
+    for suite in suites {
+        let state = suite.state.as_str();
+
+        writeln!(
+            output,
+            "<tr><td data-value=\"{0}\"><code>{0}</code></td><td data-value=\"{1}\"><span class=\"status {1}\">{1}</span></td>\
+             <td class=\"number\" data-value=\"{2}\">{2}</td><td class=\"number\" data-value=\"{3}\">{3}</td>\
+             <td class=\"number\" data-value=\"{4}\">{4}</td><td class=\"number\" data-value=\"{5}\">{6}</td></tr>",
+            escape(&suite.name),
+            state,
+            suite.passed,
+            suite.failed,
+            suite.ignored,
+            duration_value(suite.duration),
+            seconds(suite.duration)
+        )?;
+    }
+    writeln!(output, "</tbody>\n</table>")
+}
+
+/// Writes a report as a single HTML file that needs nothing else to view,
+/// for archiving with CI artifacts. Suites and the modules of their tests
+/// collapse, tests can be filtered by status and searched by name, failed
+/// tests show their output, and the suite and test tables sort by any
+/// column, time included.
+pub fn html_writer<W: Write>(suites: &[Suite], mut output: W) -> io::Result<()> {
+    let totals = suites.totals();
+
+    writeln!(output, "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">")?;
+    writeln!(output, "<meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">")?;
+    writeln!(output, "<title>Test report</title>\n<style>{}</style>\n</head>\n<body>", STYLE)?;
+    writeln!(output, "<h1>Test report</h1>")?;
+    writeln!(
+        output,
+        "<p>{} suites: {}, {} measured</p>",
+        totals.suites,
+        counts(totals.passed, totals.failed, totals.ignored),
+        totals.measured
+    )?;
+
+    writeln!(output, "<div class=\"controls\">")?;
+    writeln!(output, "<input type=\"search\" id=\"search\" placeholder=\"Search tests by name\">")?;
+    for status in &STATUSES {
+        writeln!(
+            output,
+            "<label><input type=\"checkbox\" class=\"status-filter\" value=\"{0}\" checked> <span class=\"{0}\">{0}</span></label>",
+            status.as_str()
+        )?;
+    }
+    writeln!(output, "</div>")?;
+
+    write_summary(&mut output, suites)?;
+    for suite in suites {
+        write_suite(&mut output, suite)?;
+    }
+
+    writeln!(output, "<script>{}</script>\n</body>\n</html>", SCRIPT)?;
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, html_writer};
+    use fixtures::suites;
+
+    fn report() -> String {
+        let mut output = vec![];
+
+        html_writer(&suites(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn it_should_write_a_collapsible_report() {
+        let html = report();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<p>3 suites: 2 passed, 3 failed, 1 ignored, 0 measured</p>"));
+        assert!(html.contains("<details class=\"group module\" open>\n<summary><code>tests</code>"));
+        assert!(html.contains(
+            "<tr class=\"test\" data-status=\"pass\" data-name=\"tests::it_works\">\n\
+             <td data-value=\"it_works\">it_works</td>"
+        ));
+        assert!(html.contains("<td class=\"number\" data-value=\"0.25\">0.250s</td>"));
+        assert!(html.contains(
+            "<details open><summary>it_fails</summary><pre class=\"output\">thread &#39;tests::it_fails&#39; \
+             panicked at src/lib.rs:12:9:\n100% `a &lt; b`\n```</pre></details>"
+        ));
+        assert!(html.contains("<details class=\"group suite\">\n<summary><span class=\"status pass\">pass</span><code>app</code>"));
+        assert!(html.contains("value=\"timeout\" checked>"));
+    }
+
+    #[test]
+    fn it_should_escape_markup_and_control_characters() {
+        assert_eq!(escape("<a href='x'>&\x07\tb\n"), "&lt;a href=&#39;x&#39;&gt;&amp;\\u{7}\tb\n");
+    }
+
+    #[test]
+    fn it_should_need_no_external_assets() {
+        let html = report();
+
+        assert!(!html.contains("<link"));
+        assert!(!html.contains(" src="));
+        assert!(!html.contains("http"));
+    }
+}
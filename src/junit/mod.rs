@@ -0,0 +1,5 @@
+mod reader;
+mod writer;
+
+pub use self::reader::{junit_xml_parser, JunitError};
+pub use self::writer::junit_xml_writer;
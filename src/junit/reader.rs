@@ -0,0 +1,303 @@
+use std::error::Error;
+use std::fmt;
+use std::io::{self, Read};
+
+use xml::attribute::OwnedAttribute;
+use xml::reader::{EventReader, XmlEvent};
+
+use suite::{Suite, SuiteKind, SuiteState, Test, TestStatus};
+
+#[derive(Debug)]
+pub enum JunitError {
+    Xml(String),
+    /// Writing a report failed.
+    Io(io::Error),
+    /// A report could not be written as XML.
+    Write(String),
+}
+
+impl fmt::Display for JunitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            JunitError::Xml(ref e) => write!(f, "invalid JUnit XML: {}", e),
+            JunitError::Io(ref e) => write!(f, "could not write JUnit XML: {}", e),
+            JunitError::Write(ref e) => write!(f, "could not write JUnit XML: {}", e),
+        }
+    }
+}
+
+impl Error for JunitError {}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Text {
+    None,
+    Failure,
+    /// `system-out` or `system-err`.
+    Output,
+}
+
+fn attribute<'a>(attributes: &'a [OwnedAttribute], name: &str) -> Option<&'a str> {
+    attributes
+        .iter()
+        .find(|a| a.name.local_name == name)
+        .map(|a| a.value.as_str())
+}
+
+fn append(target: &mut Option<String>, text: &str) {
+    match *target {
+        Some(ref mut s) => s.push_str(text),
+        None => *target = Some(text.to_string()),
+    }
+}
+
+fn test_name(suite: &str, attributes: &[OwnedAttribute]) -> String {
+    let name = attribute(attributes, "name").unwrap_or("unknown");
+
+    match attribute(attributes, "classname") {
+        Some(class) if !class.is_empty() && class != suite => format!("{}::{}", class, name),
+        _ => name.to_string(),
+    }
+}
+
+/// nextest marks timeouts and aborted tests through the failure's `type`.
+fn failure_status(attributes: &[OwnedAttribute]) -> TestStatus {
+    let kind = attribute(attributes, "type").unwrap_or("").to_lowercase();
+
+    if kind.contains("timeout") {
+        TestStatus::Timeout
+    } else if kind.contains("abort") || kind.contains("crash") || kind.contains("signal") {
+        TestStatus::Crashed
+    } else {
+        TestStatus::Failed
+    }
+}
+
+fn seconds(attributes: &[OwnedAttribute]) -> Option<f64> {
+    attribute(attributes, "time").and_then(|t| t.parse().ok())
+}
+
+fn finish_suite(name: String, tests: Vec<Test>, duration: Option<f64>) -> Suite {
+    let count = |f: fn(&TestStatus) -> bool| tests.iter().filter(|t| f(&t.status)).count() as i64;
+    let passed = count(|s| *s == TestStatus::Passed || *s == TestStatus::Bench);
+    let failed = count(TestStatus::is_failure);
+    let ignored = count(|s| *s == TestStatus::Ignored);
+
+    Suite {
+        name,
+        state: if failed > 0 { SuiteState::Failed } else { SuiteState::Passed },
+        passed,
+        failed,
+        ignored,
+        measured: 0,
+        total: passed + failed + ignored,
+        tests,
+        anonymous_tests: false,
+        lossy: false,
+        package: None,
+        crate_name: None,
+        kind: SuiteKind::Unknown,
+        source: None,
+        binary: None,
+        duration,
+        unmatched_failures: vec![],
+    }
+}
+
+/// Reads a JUnit XML document, as produced by nextest, cargo2junit and most
+/// other test runners, into the same suites `cargo_test_result_parser` yields.
+///
+/// Every `testsuite` holding `testcase`s becomes a `Suite`. A `failure` or
+/// `error` element marks its test as failed and its message and body become
+/// `Test.error`, the message only when the body does not hold it already.
+/// `skipped` marks it as ignored, and `system-out` and `system-err` are kept
+/// in `Test.stdout`.
+pub fn junit_xml_parser<R: Read>(input: R) -> Result<Vec<Suite>, JunitError> {
+    let mut suites = vec![];
+    let mut open_suites: Vec<(String, Vec<Test>, Option<f64>)> = vec![];
+    let mut test: Option<Test> = None;
+    let mut text = Text::None;
+    let mut body = String::new();
+
+    for event in EventReader::new(input) {
+        match event.map_err(|e| JunitError::Xml(e.to_string()))? {
+            XmlEvent::StartElement { name, attributes, .. } => {
+                match name.local_name.as_str() {
+                    "testsuite" => {
+                        let suite_name = attribute(&attributes, "name").unwrap_or("unknown");
+                        open_suites.push((suite_name.to_string(), vec![], seconds(&attributes)));
+                    }
+                    "testcase" => {
+                        let suite_name = open_suites.last().map(|s| s.0.as_str()).unwrap_or("");
+                        test = Some(Test {
+                            name: test_name(suite_name, &attributes),
+                            status: TestStatus::Passed,
+                            duration: seconds(&attributes),
+                            ..Default::default()
+                        });
+                    }
+                    "failure" | "error" => {
+                        if let Some(ref mut t) = test {
+                            t.status = failure_status(&attributes);
+                            t.error = attribute(&attributes, "message").map(|m| m.to_string());
+                        }
+                        text = Text::Failure;
+                    }
+                    "skipped" => {
+                        if let Some(ref mut t) = test {
+                            t.status = TestStatus::Ignored;
+                        }
+                    }
+                    "system-out" | "system-err" => text = Text::Output,
+                    _ => {}
+                }
+            }
+            XmlEvent::Characters(ref s) | XmlEvent::CData(ref s) => {
+                if let Some(ref mut t) = test {
+                    match text {
+                        Text::Failure => body.push_str(s),
+                        Text::Output => append(&mut t.stdout, s),
+                        Text::None => {}
+                    }
+                }
+            }
+            XmlEvent::EndElement { name } => {
+                match name.local_name.as_str() {
+                    "testsuite" => {
+                        if let Some((name, tests, duration)) = open_suites.pop() {
+                            if !tests.is_empty() {
+                                suites.push(finish_suite(name, tests, duration));
+                            }
+                        }
+                    }
+                    "testcase" => {
+                        if let (Some(t), Some(suite)) = (test.take(), open_suites.last_mut()) {
+                            suite.1.push(t);
+                        }
+                    }
+                    "failure" | "error" => {
+                        if let Some(ref mut t) = test {
+                            let details = body.trim();
+
+                            if t.error.as_ref().is_some_and(|m| details.contains(m.as_str())) {
+                                t.error = Some(details.to_string());
+                            } else if !details.is_empty() {
+                                if t.error.is_some() {
+                                    append(&mut t.error, "\n");
+                                }
+                                append(&mut t.error, details);
+                            }
+                        }
+                        body.clear();
+                        text = Text::None;
+                    }
+                    "system-out" | "system-err" => text = Text::None,
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(suites)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::junit_xml_parser;
+    use suite::{Suite, SuiteKind, SuiteState, Test, TestStatus};
+
+    #[test]
+    fn it_should_parse_a_junit_report() {
+        let output = &b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<testsuites name=\"nextest-run\" tests=\"3\" failures=\"1\">
+    <testsuite name=\"app::integration\" tests=\"3\" failures=\"1\" skipped=\"1\">
+        <testcase name=\"it_runs_a_command\" classname=\"app::integration\" time=\"0.004\"/>
+        <testcase name=\"fail\" classname=\"app::integration\" time=\"0.002\">
+            <failure message=\"assertion failed\" type=\"test failure\">thread 'fail' panicked at tests/integration_test.rs:16:5
+</failure>
+            <system-out><![CDATA[running fail
+]]></system-out>
+        </testcase>
+        <testcase name=\"slow\" classname=\"app::integration\">
+            <skipped/>
+        </testcase>
+    </testsuite>
+</testsuites>
+"[..];
+
+        assert_eq!(
+            junit_xml_parser(output).unwrap(),
+            vec![Suite {
+                name: "app::integration".to_string(),
+                state: SuiteState::Failed,
+                passed: 1,
+                failed: 1,
+                ignored: 1,
+                measured: 0,
+                total: 3,
+                anonymous_tests: false,
+                lossy: false,
+                package: None,
+                crate_name: None,
+                kind: SuiteKind::Unknown,
+                source: None,
+                binary: None,
+                duration: None,
+                unmatched_failures: vec![],
+                tests: vec![
+                    Test {
+                        name: "it_runs_a_command".to_string(),
+                        status: TestStatus::Passed,
+                        duration: Some(0.004),
+                        ..Default::default()
+                    },
+                    Test {
+                        name: "fail".to_string(),
+                        status: TestStatus::Failed,
+                        error: Some(
+                            "assertion failed\nthread 'fail' panicked at tests/integration_test.rs:16:5"
+                                .to_string(),
+                        ),
+                        stdout: Some("running fail\n".to_string()),
+                        lossy: false,
+                        duration: Some(0.002),
+                    },
+                    Test {
+                        name: "slow".to_string(),
+                        status: TestStatus::Ignored,
+                        ..Default::default()
+                    },
+                ],
+            }]
+        );
+    }
+
+    #[test]
+    fn it_should_qualify_names_with_a_foreign_classname() {
+        let output = &b"<testsuite name=\"api\">
+<testcase name=\"testLogin\" classname=\"com.example.AuthTest\"/>
+</testsuite>"[..];
+
+        let suites = junit_xml_parser(output).unwrap();
+
+        assert_eq!(suites[0].tests[0].name, "com.example.AuthTest::testLogin");
+    }
+
+    #[test]
+    fn it_should_read_timeouts_from_the_failure_type() {
+        let output = &b"<testsuite name=\"api\">
+<testcase name=\"it_hangs\"><failure type=\"test timeout\"/></testcase>
+</testsuite>"[..];
+
+        let suites = junit_xml_parser(output).unwrap();
+
+        assert_eq!(suites[0].tests[0].status, TestStatus::Timeout);
+        assert_eq!(suites[0].state, SuiteState::Failed);
+        assert_eq!(suites[0].failed, 1);
+    }
+
+    #[test]
+    fn it_should_reject_malformed_xml() {
+        assert!(junit_xml_parser(&b"<testsuite name=\"api\"><testcase>"[..]).is_err());
+    }
+}
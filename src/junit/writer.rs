@@ -0,0 +1,225 @@
+use std::io::Write;
+
+use xml::writer::{self, EmitterConfig, EventWriter, XmlEvent};
+
+use ansi::{plain, spell_out};
+use junit::JunitError;
+use suite::{Suite, Test, TestStatus};
+
+/// XML 1.0 cannot hold most control characters, not even as character
+/// references.
+fn is_xml_char(c: char) -> bool {
+    match c {
+        '\t' | '\n' | '\r' => true,
+        '\u{0}'..='\u{1f}' | '\u{fffe}' | '\u{ffff}' => false,
+        _ => true,
+    }
+}
+
+/// `text` without ANSI escapes, with the other control characters spelled
+/// out.
+fn xml_text(text: &str) -> String {
+    spell_out(&plain(text), is_xml_char).into_owned()
+}
+
+fn seconds(duration: f64) -> String {
+    format!("{:.3}", duration)
+}
+
+/// The `type` `junit_xml_parser` reads the status back from.
+fn failure_type(status: TestStatus) -> &'static str {
+    match status {
+        TestStatus::Timeout => "test timeout",
+        TestStatus::Crashed => "test crashed",
+        _ => "test failure",
+    }
+}
+
+fn text_element<W: Write>(writer: &mut EventWriter<W>, name: &str, text: &str) -> writer::Result<()> {
+    writer.write(XmlEvent::start_element(name))?;
+    writer.write(XmlEvent::characters(&xml_text(text)))?;
+    writer.write(XmlEvent::end_element())
+}
+
+fn write_test<W: Write>(writer: &mut EventWriter<W>, suite: &Suite, test: &Test) -> writer::Result<()> {
+    let name = xml_text(&test.name);
+    let classname = xml_text(&suite.name);
+    let time = test.duration.map(seconds);
+    let mut element = XmlEvent::start_element("testcase")
+        .attr("name", &name)
+        .attr("classname", &classname);
+    if let Some(ref time) = time {
+        element = element.attr("time", time);
+    }
+    writer.write(element)?;
+
+    if test.status.is_failure() {
+        let message = test.panic_message().map(xml_text);
+        let mut element = XmlEvent::start_element("failure").attr("type", failure_type(test.status));
+        if let Some(ref message) = message {
+            element = element.attr("message", message);
+        }
+
+        writer.write(element)?;
+        if let Some(ref error) = test.error {
+            writer.write(XmlEvent::characters(&xml_text(error)))?;
+        }
+        writer.write(XmlEvent::end_element())?;
+    } else if test.status == TestStatus::Ignored {
+        writer.write(XmlEvent::start_element("skipped"))?;
+        writer.write(XmlEvent::end_element())?;
+    }
+    if let Some(ref stdout) = test.stdout {
+        text_element(writer, "system-err", stdout)?;
+    }
+
+    writer.write(XmlEvent::end_element())
+}
+
+fn write_suite<W: Write>(writer: &mut EventWriter<W>, suite: &Suite) -> writer::Result<()> {
+    let name = xml_text(&suite.name);
+    let tests = suite.total.to_string();
+    let failures = suite.failed.to_string();
+    let skipped = suite.ignored.to_string();
+    let time = suite.duration.map(seconds);
+    let mut element = XmlEvent::start_element("testsuite")
+        .attr("name", &name)
+        .attr("tests", &tests)
+        .attr("failures", &failures)
+        .attr("errors", "0")
+        .attr("skipped", &skipped);
+    if let Some(ref time) = time {
+        element = element.attr("time", time);
+    }
+    writer.write(element)?;
+
+    for test in &suite.tests {
+        write_test(writer, suite, test)?;
+    }
+
+    writer.write(XmlEvent::end_element())
+}
+
+fn write_suites<W: Write>(writer: &mut EventWriter<W>, suites: &[Suite]) -> writer::Result<()> {
+    let count = |f: fn(&Suite) -> i64| suites.iter().map(f).sum::<i64>().to_string();
+    let tests = count(|s| s.total);
+    let failures = count(|s| s.failed);
+    let skipped = count(|s| s.ignored);
+    let time = match suites.iter().filter_map(|s| s.duration).collect::<Vec<_>>() {
+        ref durations if durations.is_empty() => None,
+        durations => Some(seconds(durations.iter().sum())),
+    };
+    let mut element = XmlEvent::start_element("testsuites")
+        .attr("name", "cargo test")
+        .attr("tests", &tests)
+        .attr("failures", &failures)
+        .attr("errors", "0")
+        .attr("skipped", &skipped);
+    if let Some(ref time) = time {
+        element = element.attr("time", time);
+    }
+    writer.write(element)?;
+
+    for suite in suites {
+        write_suite(writer, suite)?;
+    }
+
+    writer.write(XmlEvent::end_element())
+}
+
+/// Writes suites as a JUnit XML report, for Jenkins, GitLab, Azure DevOps
+/// and the like. `time` attributes are written where durations are known.
+///
+/// libtest merges a test's stdout and stderr into one capture. A failed
+/// test's capture, its `Test.error`, is the body of its `failure`, with the
+/// panic message as its `message`; output kept in `Test.stdout` goes to
+/// `system-err`. Every `testcase` has its suite's name as `classname`, which
+/// `junit_xml_parser` leaves out of test names.
+pub fn junit_xml_writer<W: Write>(suites: &[Suite], output: W) -> Result<(), JunitError> {
+    let mut writer = EmitterConfig::new().perform_indent(true).create_writer(output);
+
+    write_suites(&mut writer, suites).map_err(|e| match e {
+        writer::Error::Io(e) => JunitError::Io(e),
+        e => JunitError::Write(e.to_string()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str;
+
+    use super::junit_xml_writer;
+    use ansi::plain;
+    use fixtures::suites;
+    use junit::junit_xml_parser;
+    use suite::{Suite, Test};
+
+    #[test]
+    fn it_should_write_a_junit_report() {
+        let mut output = vec![];
+
+        junit_xml_writer(&suites(), &mut output).unwrap();
+
+        let xml = str::from_utf8(&output).unwrap();
+        assert!(xml.contains(
+            "<testsuites name=\"cargo test\" tests=\"6\" failures=\"3\" errors=\"0\" skipped=\"1\" time=\"1.500\">"
+        ));
+        assert!(xml.contains(
+            "<testcase name=\"tests::it_works\" \
+             classname=\"unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)\" time=\"0.250\" />"
+        ));
+        assert!(xml.contains(
+            "<failure type=\"test failure\" message=\"100% `a &lt; b`&#xA;```\">\
+             thread 'tests::it_fails' panicked at src/lib.rs:12:9:\n100% `a &lt; b`\n```</failure>"
+        ));
+        assert!(xml.contains("<failure type=\"test timeout\" />"));
+        assert!(xml.contains("<skipped />"));
+    }
+
+    #[test]
+    fn it_should_write_captured_output_to_system_err() {
+        let suites = vec![Suite {
+            name: "app".to_string(),
+            total: 1,
+            passed: 1,
+            tests: vec![Test {
+                name: "it_prints".to_string(),
+                stdout: Some("printed\x07\n".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }];
+        let mut output = vec![];
+
+        junit_xml_writer(&suites, &mut output).unwrap();
+
+        assert!(str::from_utf8(&output)
+            .unwrap()
+            .contains("<system-err>printed\\u{7}\n</system-err>"));
+        assert_eq!(
+            junit_xml_parser(&output[..]).unwrap()[0].tests[0].stdout.as_deref(),
+            Some("printed\\u{7}\n")
+        );
+    }
+
+    #[test]
+    fn it_should_read_its_own_reports_back() {
+        let original = suites();
+        let mut output = vec![];
+        junit_xml_writer(&original, &mut output).unwrap();
+
+        let suites = junit_xml_parser(&output[..]).unwrap();
+
+        assert_eq!(suites.len(), original.len());
+        for (suite, original) in suites.iter().zip(&original) {
+            assert_eq!(suite.name, original.name);
+            assert_eq!((suite.failed, suite.duration), (original.failed, original.duration));
+            for (test, original) in suite.tests.iter().zip(&original.tests) {
+                assert_eq!(test.name, original.name);
+                assert_eq!(test.status, original.status);
+                assert_eq!(test.duration, original.duration);
+                assert_eq!(test.error, original.error.as_ref().map(|e| plain(e)));
+            }
+        }
+    }
+}
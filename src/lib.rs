@@ -1,18 +1,81 @@
+pub mod borrowed;
+mod annotation;
+mod ansi;
+mod ci;
 mod header;
+mod html;
 mod utility_parsers;
 mod suite;
+mod junit;
+mod markdown;
+mod messages;
+mod parse;
+mod query;
+mod run;
+mod sarif;
+mod tap;
+mod uri;
+#[cfg(test)]
+mod fixtures;
 
+extern crate memchr;
 #[macro_use]
 extern crate nom;
+#[cfg(feature = "serde")]
+extern crate serde;
+extern crate serde_json;
+extern crate xml;
 
+use std::borrow::Cow;
 use std::str;
+use nom::{line_ending, not_line_ending};
 
 use header::cargo_header;
-pub use suite::{Suite, Test};
-use suite::suites_parser;
+use utility_parsers::{decode, map_text};
+pub use annotation::{annotation_writer, annotations, Annotation, AnnotationFormat, AnnotationLevel};
+pub use ansi::{strip_ansi, Colors};
+pub use ci::{strip_ci_prefixes, CiLog, CiPrefix, Timing};
+pub use suite::{
+    group_by_package, Discrepancy, Location, ModuleNode, Suite, SuiteKind, SuiteState, Test, TestId, TestStatus,
+};
+pub use html::html_writer;
+pub use junit::{junit_xml_parser, junit_xml_writer, JunitError};
+pub use markdown::markdown_writer;
+pub use messages::{
+    cargo_json_parser, Artifact, BuildEvent, Diagnostic, DiagnosticSpan, JsonRun, Target,
+};
+pub use parse::{parse, Expected, ParseError};
+pub use query::{Query, Tests, Totals};
+pub use run::{TestRun, SCHEMA_VERSION};
+pub use sarif::sarif_writer;
+pub use tap::tap_writer;
+use suite::{assign_packages, into_owned, suites_parser, COMPILE_FAILED};
+
+fn compile_error_text(body: Cow<str>) -> Cow<str> {
+    if !body.contains("\r\n") {
+        return map_text(body, |b| b.strip_suffix('\n').unwrap_or(b));
+    }
+
+    let body = body.replace("\r\n", "\n");
+
+    match body.strip_suffix('\n') {
+        Some(text) => Cow::Owned(text.to_string()),
+        None => Cow::Owned(body),
+    }
+}
 
 named!(
-  compile_error<Vec<Suite > >,
+  compile_error_line<&[u8], ()>,
+  do_parse!(
+    not!(complete!(tag!("error"))) >>
+    not_line_ending >>
+    line_ending >>
+    ()
+  )
+);
+
+named!(
+  compile_error<&[u8], Vec<borrowed::Suite<'_> > >,
   do_parse!(
     vector: many_till!(
       do_parse!(
@@ -20,33 +83,46 @@ named!(
         opt_res!(
           do_parse!(
             char!('[') >>
-            take_until_and_consume!("]") >>
+            take_till!(|c| c == b']' || c == b'\n') >>
+            char!(']') >>
             ()
           )
         ) >>
         ws!(char!(':')) >>
-        error: map_res!(
-                take_until!("\nerror"),
-                str::from_utf8
+        error: map!(
+                recognize!(many0!(complete!(compile_error_line))),
+                decode
             ) >>
-        (Suite {
-            name: "unknown".to_string(),
-            state: "fail".to_string(),
+        (borrowed::Suite {
+            name: "unknown".into(),
+            state: SuiteState::Failed,
             total: 1,
             passed: 0,
             failed: 1,
             ignored: 0,
             measured: 0,
+            anonymous_tests: false,
+            lossy: error.1,
+            package: None,
+            crate_name: None,
+            kind: SuiteKind::Unknown,
+            source: None,
+            binary: None,
+            duration: None,
+            unmatched_failures: vec![],
             tests: vec![
-              Test {
-                name: "compile failed".to_string(),
-                status: "fail".to_string(),
-                error: Some(error.into())
+              borrowed::Test {
+                name: COMPILE_FAILED.into(),
+                status: TestStatus::Failed,
+                lossy: error.1,
+                error: Some(compile_error_text(error.0)),
+                stdout: None,
+                duration: None
               }
             ]
         })
       ),
-      tag!("\nerror: aborting due to previous error")
+      tag!("error: aborting due to")
     ) >>
     take_till!(|c| c == 0x0) >>
     (vector.0)
@@ -56,8 +132,11 @@ named!(
 named!(
     pub cargo_test_result_parser<Vec<Suite > >,
     do_parse!(
-        cargo_header >>
-        suites: alt!(suites_parser | compile_error) >>
-        (suites)
+        packages: cargo_header >>
+        suites: alt!(
+            map!(suites_parser, |suites| assign_packages(suites, &packages)) |
+            compile_error
+        ) >>
+        (into_owned(suites))
     )
 );
@@ -0,0 +1,239 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use ansi::plain;
+use query::Query;
+use suite::{compile_error_location, Location, Suite, SuiteState, Test};
+use uri::{is_absolute, relative_uri};
+
+/// Pipes end a table cell even inside code spans.
+fn cell(text: &str) -> Cow<'_, str> {
+    if !text.contains('|') {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.replace('|', "\\|"))
+}
+
+fn html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn seconds(duration: Option<f64>) -> String {
+    duration.map_or_else(|| "–".to_string(), |d| format!("{:.2}s", d))
+}
+
+/// A fence longer than any run of backticks in `text`.
+pub fn fence(text: &str) -> String {
+    let longest = text.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+
+    "`".repeat(longest.max(2) + 1)
+}
+
+/// Fills `{file}`, `{line}` and `{column}` in a link template. Absolute
+/// paths, of dependencies and the standard library, are not in the
+/// repository and get no link.
+fn source_link(template: &str, location: &Location) -> Option<String> {
+    if is_absolute(location.file) {
+        return None;
+    }
+
+    Some(
+        template
+            .replace("{file}", &relative_uri(location.file))
+            .replace("{line}", &location.line.to_string())
+            .replace("{column}", &location.column.unwrap_or(1).to_string()),
+    )
+}
+
+fn write_totals<W: Write>(output: &mut W, suites: &[Suite]) -> io::Result<()> {
+    let totals = suites.totals();
+    let icon = if totals.failed == 0 { "✅" } else { "❌" };
+    let pass_rate = totals.pass_rate().map_or_else(|| "–".to_string(), |r| format!("{:.1}%", r * 100.0));
+
+    writeln!(output, "## {} Test results\n", icon)?;
+    writeln!(output, "| Suites | Passed | Failed | Ignored | Measured | Pass rate |")?;
+    writeln!(output, "| -----: | -----: | -----: | ------: | -------: | --------: |")?;
+    writeln!(
+        output,
+        "| {} | {} | {} | {} | {} | {} |\n",
+        totals.suites, totals.passed, totals.failed, totals.ignored, totals.measured, pass_rate
+    )
+}
+
+fn write_suites<W: Write>(output: &mut W, suites: &[Suite]) -> io::Result<()> {
+    writeln!(output, "| | Suite | Passed | Failed | Ignored | Time |")?;
+    writeln!(output, "| - | :---- | -----: | -----: | ------: | ---: |")?;
+    for suite in suites {
+        let icon = match suite.state {
+            SuiteState::Passed => "✅",
+            SuiteState::Failed => "❌",
+        };
+        writeln!(
+            output,
+            "| {} | `{}` | {} | {} | {} | {} |",
+            icon,
+            cell(&suite.name),
+            suite.passed,
+            suite.failed,
+            suite.ignored,
+            seconds(suite.duration)
+        )?;
+    }
+    writeln!(output)
+}
+
+fn write_failure<W: Write>(output: &mut W, suite: &Suite, test: &Test, source_url: Option<&str>) -> io::Result<()> {
+    writeln!(output, "<details>")?;
+    writeln!(
+        output,
+        "<summary><code>{}</code> in <code>{}</code> ({})</summary>\n",
+        html(&test.name),
+        html(&suite.name),
+        test.status
+    )?;
+
+    let location = if test.is_compile_error() {
+        test.error.as_ref().and_then(|e| compile_error_location(e))
+    } else {
+        test.panic_location()
+    };
+    if let Some(location) = location {
+        let mut at = format!("{}:{}", location.file, location.line);
+        if let Some(column) = location.column {
+            at.push_str(&format!(":{}", column));
+        }
+        match source_url.and_then(|template| source_link(template, &location)) {
+            Some(link) => writeln!(output, "at [`{}`]({})\n", at, link)?,
+            None => writeln!(output, "at `{}`\n", at)?,
+        }
+    }
+    if let Some(message) = test.panic_message() {
+        let message = plain(message);
+        let fence = fence(&message);
+
+        writeln!(output, "{}text\n{}\n{}\n", fence, message.trim_end(), fence)?;
+    }
+
+    writeln!(output, "</details>\n")
+}
+
+/// Writes a compact Markdown report for GitHub step summaries and pull
+/// request comments: a totals table, a row per suite and a collapsed
+/// `<details>` block per failed test with its panic message and location.
+///
+/// Locations link to `source_url` when given, a template such as
+/// `https://github.com/owner/repo/blob/<sha>/{file}#L{line}`; `{column}`
+/// is filled in as well.
+pub fn markdown_writer<W: Write>(suites: &[Suite], source_url: Option<&str>, mut output: W) -> io::Result<()> {
+    write_totals(&mut output, suites)?;
+    if !suites.is_empty() {
+        write_suites(&mut output, suites)?;
+    }
+
+    let failed = suites.failed_tests();
+    if !failed.is_empty() {
+        writeln!(output, "### Failures\n")?;
+        for (suite, test) in failed {
+            write_failure(&mut output, suite, test, source_url)?;
+        }
+    }
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str;
+
+    use super::{markdown_writer, source_link};
+    use fixtures::suites;
+    use suite::Location;
+
+    #[test]
+    fn it_should_render_a_summary() {
+        let mut output = vec![];
+
+        markdown_writer(
+            &suites(),
+            Some("https://github.com/owner/app/blob/main/{file}#L{line}"),
+            &mut output,
+        )
+        .unwrap();
+
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "## ❌ Test results
+
+| Suites | Passed | Failed | Ignored | Measured | Pass rate |
+| -----: | -----: | -----: | ------: | -------: | --------: |
+| 3 | 2 | 3 | 1 | 0 | 40.0% |
+
+| | Suite | Passed | Failed | Ignored | Time |
+| - | :---- | -----: | -----: | ------: | ---: |
+| ❌ | `unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)` | 1 | 2 | 1 | 1.50s |
+| ✅ | `app` | 1 | 0 | 0 | – |
+| ❌ | `unknown` | 0 | 1 | 0 | – |
+
+### Failures
+
+<details>
+<summary><code>tests::it_fails</code> in <code>unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)</code> (fail)</summary>
+
+at [`src/lib.rs:12:9`](https://github.com/owner/app/blob/main/src/lib.rs#L12)
+
+````text
+100% `a < b`
+```
+````
+
+</details>
+
+<details>
+<summary><code>tests::it_hangs</code> in <code>unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)</code> (timeout)</summary>
+
+</details>
+
+<details>
+<summary><code>compile failed</code> in <code>unknown</code> (fail)</summary>
+
+at [`src/main.rs:3:5`](https://github.com/owner/app/blob/main/src/main.rs#L3)
+
+```text
+mismatched types
+```
+
+</details>
+
+"
+        );
+    }
+
+    #[test]
+    fn it_should_leave_locations_unlinked_without_a_url() {
+        let mut output = vec![];
+
+        markdown_writer(&suites(), None, &mut output).unwrap();
+
+        assert!(str::from_utf8(&output).unwrap().contains("\nat `src/lib.rs:12:9`\n"));
+    }
+
+    #[test]
+    fn it_should_link_only_relative_paths() {
+        let template = "https://github.com/owner/app/blob/main/{file}#L{line}";
+        let link = |file| {
+            let location = Location {
+                file,
+                line: 3,
+                column: Some(5),
+            };
+            source_link(template, &location)
+        };
+
+        assert_eq!(
+            link("tests/my tests (v2)#1.rs"),
+            Some("https://github.com/owner/app/blob/main/tests/my%20tests%20%28v2%29%231.rs#L3".to_string())
+        );
+        assert_eq!(link("/home/user/.cargo/registry/src/serde-1.0.0/src/de.rs"), None);
+        assert_eq!(link("/rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/core/src/option.rs"), None);
+        assert_eq!(link("C:\\Users\\user\\.cargo\\registry\\src\\de.rs"), None);
+    }
+}
@@ -0,0 +1,366 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use serde_json::{self, Value};
+use std::borrow::Cow;
+
+use header::header_line;
+use suite::{binary_path, into_owned, suites_parser, Suite, SuiteState, Test, TestStatus, COMPILE_FAILED};
+use utility_parsers::rest_of_line;
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Target {
+    pub name: String,
+    pub kind: Vec<String>,
+    pub src_path: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Artifact {
+    pub package_id: String,
+    pub target: Target,
+    pub executable: Option<String>,
+    pub fresh: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DiagnosticSpan {
+    pub file_name: String,
+    pub line_start: u64,
+    pub line_end: u64,
+    pub column_start: u64,
+    pub column_end: u64,
+    pub is_primary: bool,
+    pub label: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Diagnostic {
+    pub level: String,
+    pub message: String,
+    pub code: Option<String>,
+    pub spans: Vec<DiagnosticSpan>,
+    pub rendered: Option<String>,
+}
+
+/// Serialized like cargo's own records, tagged with their `reason`.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(Serialize, Deserialize),
+    serde(tag = "reason", rename_all = "kebab-case")
+)]
+pub enum BuildEvent {
+    CompilerMessage {
+        package_id: String,
+        target: Target,
+        diagnostic: Diagnostic,
+    },
+    CompilerArtifact(Artifact),
+    BuildScriptExecuted { package_id: String, out_dir: String },
+    BuildFinished { success: bool },
+}
+
+/// The outcome of `cargo test --message-format=json`: the build records cargo
+/// printed as JSON, followed by the suites libtest printed as usual.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JsonRun {
+    pub events: Vec<BuildEvent>,
+    pub suites: Vec<Suite>,
+}
+
+/// Extracts the package name from a cargo package id, in both the old
+/// `foo 0.1.0 (path+file:///foo)` and the newer `path+file:///foo#0.1.0`
+/// or `registry+https://...#foo@0.1.0` forms.
+pub fn package_name(package_id: &str) -> &str {
+    match package_id.find('#') {
+        Some(hash) => {
+            let fragment = &package_id[hash + 1..];
+
+            match fragment.find('@') {
+                Some(at) => &fragment[..at],
+                None => {
+                    let url = package_id[..hash].trim_end_matches('/');
+                    url.rsplit('/').next().unwrap_or(url)
+                }
+            }
+        }
+        None => package_id.split_whitespace().next().unwrap_or(package_id),
+    }
+}
+
+impl Artifact {
+    pub fn package_name(&self) -> &str {
+        package_name(&self.package_id)
+    }
+}
+
+fn is_same_binary(executable: &str, binary: &str) -> bool {
+    let executable = executable.replace('\\', "/");
+    let binary = binary.replace('\\', "/");
+
+    executable == binary || executable.ends_with(&format!("/{}", binary))
+}
+
+fn is_doc_library(artifact: &Artifact, crate_name: &str) -> bool {
+    let target = &artifact.target;
+
+    artifact.executable.is_none()
+        && target.name.replace('-', "_") == crate_name.replace('-', "_")
+        && target.kind.iter().any(|k| k.ends_with("lib") || k == "proc-macro")
+}
+
+impl JsonRun {
+    pub fn artifacts(&self) -> impl Iterator<Item = &Artifact> {
+        self.events.iter().filter_map(|e| match *e {
+            BuildEvent::CompilerArtifact(ref a) => Some(a),
+            _ => None,
+        })
+    }
+
+    pub fn diagnostics(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.events.iter().filter_map(|e| match *e {
+            BuildEvent::CompilerMessage { ref diagnostic, .. } => Some(diagnostic),
+            _ => None,
+        })
+    }
+
+    /// Finds the artifact a suite was run from: the test executable whose path
+    /// `suite_line` reported, or the library a `Doc-tests` suite was built against.
+    pub fn artifact_for(&self, suite: &Suite) -> Option<&Artifact> {
+        let binary = binary_path(&suite.name);
+
+        self.artifacts()
+            .find(|a| a.executable.as_ref().is_some_and(|e| is_same_binary(e, binary)))
+            .or_else(|| self.artifacts().find(|a| is_doc_library(a, &suite.name)))
+    }
+}
+
+fn string(value: &Value, key: &str) -> String {
+    value.get(key).and_then(Value::as_str).unwrap_or("").to_string()
+}
+
+fn optional_string(value: &Value, key: &str) -> Option<String> {
+    value.get(key).and_then(Value::as_str).map(|s| s.to_string())
+}
+
+fn number(value: &Value, key: &str) -> u64 {
+    value.get(key).and_then(Value::as_u64).unwrap_or(0)
+}
+
+fn target(value: &Value) -> Target {
+    Target {
+        name: string(value, "name"),
+        kind: value
+            .get("kind")
+            .and_then(Value::as_array)
+            .map(|kinds| kinds.iter().filter_map(Value::as_str).map(|k| k.to_string()).collect())
+            .unwrap_or_default(),
+        src_path: string(value, "src_path"),
+    }
+}
+
+fn diagnostic_span(value: &Value) -> DiagnosticSpan {
+    DiagnosticSpan {
+        file_name: string(value, "file_name"),
+        line_start: number(value, "line_start"),
+        line_end: number(value, "line_end"),
+        column_start: number(value, "column_start"),
+        column_end: number(value, "column_end"),
+        is_primary: value.get("is_primary").and_then(Value::as_bool).unwrap_or(false),
+        label: optional_string(value, "label"),
+    }
+}
+
+fn diagnostic(value: &Value) -> Diagnostic {
+    Diagnostic {
+        level: string(value, "level"),
+        message: string(value, "message"),
+        code: value.get("code").and_then(|c| optional_string(c, "code")),
+        spans: value
+            .get("spans")
+            .and_then(Value::as_array)
+            .map(|spans| spans.iter().map(diagnostic_span).collect())
+            .unwrap_or_default(),
+        rendered: optional_string(value, "rendered"),
+    }
+}
+
+fn record(line: &str) -> Option<Value> {
+    if !line.starts_with('{') {
+        return None;
+    }
+
+    serde_json::from_str::<Value>(line)
+        .ok()
+        .and_then(|v| if v.get("reason").is_some() { Some(v) } else { None })
+}
+
+fn build_event(record: &Value) -> Option<BuildEvent> {
+    let event = match record.get("reason").and_then(Value::as_str)? {
+        "compiler-message" => BuildEvent::CompilerMessage {
+            package_id: string(record, "package_id"),
+            target: record.get("target").map(target)?,
+            diagnostic: record.get("message").map(diagnostic)?,
+        },
+        "compiler-artifact" => BuildEvent::CompilerArtifact(Artifact {
+            package_id: string(record, "package_id"),
+            target: record.get("target").map(target)?,
+            executable: optional_string(record, "executable"),
+            fresh: record.get("fresh").and_then(Value::as_bool).unwrap_or(false),
+        }),
+        "build-script-executed" => BuildEvent::BuildScriptExecuted {
+            package_id: string(record, "package_id"),
+            out_dir: string(record, "out_dir"),
+        },
+        "build-finished" => BuildEvent::BuildFinished {
+            success: record.get("success").and_then(Value::as_bool).unwrap_or(false),
+        },
+        _ => return None,
+    };
+
+    Some(event)
+}
+
+/// A suite per compiler error. Span-less errors such as `aborting due to 1
+/// previous error` only sum up the others.
+fn compile_failures(events: &[BuildEvent]) -> Vec<Suite> {
+    events
+        .iter()
+        .filter_map(|e| match *e {
+            BuildEvent::CompilerMessage { ref package_id, ref target, ref diagnostic }
+                if diagnostic.level == "error" && !diagnostic.spans.is_empty() =>
+            {
+                Some((package_id, target, diagnostic))
+            }
+            _ => None,
+        })
+        .map(|(package_id, target, d)| {
+            let error = match d.rendered {
+                Some(ref rendered) => rendered.split_once(": ").map_or(rendered.as_str(), |(_, e)| e),
+                None => &d.message,
+            };
+
+            Suite {
+                name: "unknown".to_string(),
+                state: SuiteState::Failed,
+                total: 1,
+                failed: 1,
+                tests: vec![Test {
+                    name: COMPILE_FAILED.to_string(),
+                    status: TestStatus::Failed,
+                    error: Some(error.to_string()),
+                    ..Default::default()
+                }],
+                package: Some(package_name(package_id).to_string()),
+                crate_name: Some(target.name.replace('-', "_")),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+fn json_run(events: Vec<Option<BuildEvent>>, suites: Option<Vec<Suite>>) -> JsonRun {
+    let events: Vec<BuildEvent> = events.into_iter().flatten().collect();
+    let suites = match suites {
+        Some(suites) => suites,
+        None => compile_failures(&events),
+    };
+    let mut run = JsonRun { events, suites: vec![] };
+
+    run.suites = suites
+        .into_iter()
+        .map(|mut suite| {
+            if suite.package.is_none() {
+                suite.package = run.artifact_for(&suite).map(|a| a.package_name().to_string());
+            }
+            suite
+        })
+        .collect();
+
+    run
+}
+
+named!(
+    json_line<Option<BuildEvent> >,
+    map!(
+        map_opt!(ws!(rest_of_line), |line: Cow<str>| record(&line)),
+        |r| build_event(&r)
+    )
+);
+
+named!(
+    pub cargo_json_parser<JsonRun>,
+    do_parse!(
+        events: many0!(
+            complete!(
+                alt!(json_line | map!(header_line, |_| None))
+            )
+        ) >>
+        suites: opt!(complete!(map!(suites_parser, into_owned))) >>
+        (json_run(events, suites))
+    )
+);
+
+#[cfg(test)]
+mod tests {
+    use super::{build_event, json_line, package_name, record, BuildEvent, DiagnosticSpan};
+    use nom::IResult;
+
+    #[test]
+    fn it_should_extract_package_names() {
+        assert_eq!(package_name("foo 0.1.0 (path+file:///work/foo)"), "foo");
+        assert_eq!(package_name("path+file:///work/foo-bar#0.1.0"), "foo-bar");
+        assert_eq!(package_name("path+file:///work/crates/api#api-server@0.1.0"), "api-server");
+        assert_eq!(
+            package_name("registry+https://github.com/rust-lang/crates.io-index#serde@1.0.102"),
+            "serde"
+        );
+    }
+
+    #[test]
+    fn it_should_parse_a_build_finished_line() {
+        assert_eq!(
+            json_line(&b"{\"reason\":\"build-finished\",\"success\":true}\n"[..]),
+            IResult::Done(&b""[..], Some(BuildEvent::BuildFinished { success: true }))
+        );
+    }
+
+    #[test]
+    fn it_should_skip_unknown_reasons() {
+        assert_eq!(
+            json_line(&b"{\"reason\":\"timing-info\",\"duration\":0.5}\n"[..]),
+            IResult::Done(&b""[..], None)
+        );
+    }
+
+    #[test]
+    fn it_should_parse_a_compiler_message() {
+        let line = r#"{"reason":"compiler-message","package_id":"path+file:///work/app#0.1.0","target":{"kind":["lib"],"crate_types":["lib"],"name":"app","src_path":"/work/app/src/lib.rs"},"message":{"rendered":"error[E0369]: binary operation `==` cannot be applied\n --> src/lib.rs:3:5\n","children":[],"code":{"code":"E0369","explanation":null},"level":"error","message":"binary operation `==` cannot be applied","spans":[{"byte_end":40,"byte_start":30,"column_end":15,"column_start":5,"expansion":null,"file_name":"src/lib.rs","is_primary":true,"label":"no implementation for `==`","line_end":3,"line_start":3,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}"#;
+
+        match record(line).and_then(|r| build_event(&r)) {
+            Some(BuildEvent::CompilerMessage { package_id, target, diagnostic }) => {
+                assert_eq!(package_id, "path+file:///work/app#0.1.0");
+                assert_eq!(target.kind, vec!["lib".to_string()]);
+                assert_eq!(diagnostic.code, Some("E0369".to_string()));
+                assert_eq!(
+                    diagnostic.spans,
+                    vec![DiagnosticSpan {
+                        file_name: "src/lib.rs".to_string(),
+                        line_start: 3,
+                        line_end: 3,
+                        column_start: 5,
+                        column_end: 15,
+                        is_primary: true,
+                        label: Some("no implementation for `==`".to_string()),
+                    }]
+                );
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use nom::IResult;
+use std::error::Error;
+use std::fmt;
+
+use compile_error;
+use header::cargo_header;
+use run::TestRun;
+use borrowed;
+use suite::{assign_packages, into_owned, parse_suite};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expected {
+    /// `Running ...` or `Doc-tests ...`.
+    SuiteHeader,
+    /// `running 3 tests`.
+    TestCount,
+    /// `failures:` followed by the captured output of each failed test.
+    FailureSection,
+    /// `test result: ok. 3 passed; ...`.
+    ResultLine,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// 1-based line of the log.
+    pub line: usize,
+    /// 1-based column within `text`, in characters.
+    pub column: usize,
+    /// The offending line, without its line ending.
+    pub text: String,
+    pub expected: Expected,
+}
+
+impl fmt::Display for Expected {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match *self {
+            Expected::SuiteHeader => "a suite header (`Running ...` or `Doc-tests ...`)",
+            Expected::TestCount => "a test count (`running N tests`)",
+            Expected::FailureSection => "a failure section",
+            Expected::ResultLine => "a result line (`test result: ...`)",
+        })
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: expected {}, found `{}`",
+            self.line, self.column, self.expected, self.text
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+impl ParseError {
+    /// Locates `rest`, the input left when parsing stopped, skipping the
+    /// blank space every parser would have skipped too.
+    fn at(input: &[u8], rest: &[u8], expected: Expected) -> ParseError {
+        let offset = input.len() - rest.len();
+        let offset = offset + rest.iter().take_while(|c| c.is_ascii_whitespace()).count();
+        let line_start = input[..offset].iter().rposition(|&c| c == b'\n').map_or(0, |i| i + 1);
+        let line_end = input[offset..]
+            .iter()
+            .position(|&c| c == b'\n')
+            .map_or(input.len(), |i| offset + i);
+        let text = String::from_utf8_lossy(&input[line_start..line_end]);
+
+        ParseError {
+            line: input[..offset].iter().filter(|&&c| c == b'\n').count() + 1,
+            column: String::from_utf8_lossy(&input[line_start..offset]).chars().count() + 1,
+            text: text.trim_end_matches('\r').to_string(),
+            expected,
+        }
+    }
+}
+
+/// Parses the output of `cargo test` into a `TestRun`. Unlike
+/// `cargo_test_result_parser` it needs no knowledge of nom, and on failure
+/// says where the log stopped making sense. Anything after the last suite,
+/// such as cargo's `error: test failed` summary, is ignored.
+pub fn parse(input: &[u8]) -> Result<TestRun, ParseError> {
+    parse_suites(input).map(|suites| TestRun::new(into_owned(suites)))
+}
+
+pub fn parse_suites(input: &[u8]) -> Result<Vec<borrowed::Suite<'_>>, ParseError> {
+    let (mut rest, packages) = match cargo_header(input) {
+        IResult::Done(rest, packages) => (rest, packages),
+        _ => (input, vec![]),
+    };
+    let mut suites = vec![];
+
+    loop {
+        match parse_suite(rest) {
+            Ok((remaining, suite)) => {
+                suites.push(suite);
+                rest = remaining;
+            }
+            Err((_, Expected::SuiteHeader)) if !suites.is_empty() => break,
+            Err((at, expected)) => {
+                if suites.is_empty() {
+                    if let IResult::Done(_, suites) = compile_error(rest) {
+                        return Ok(suites);
+                    }
+                }
+                return Err(ParseError::at(input, at, expected));
+            }
+        }
+    }
+
+    Ok(assign_packages(suites, &packages))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse, Expected, ParseError};
+
+    #[test]
+    fn it_should_point_at_a_missing_result_line() {
+        let output = b"    Finished dev [unoptimized + debuginfo] target(s) in 0.0 secs
+     Running target/debug/deps/app-622eefdc86aa5319
+
+running 1 test
+test it_works ... ok
+thread 'main' panicked at 'stack overflow'
+";
+
+        assert_eq!(
+            parse(output),
+            Err(ParseError {
+                line: 6,
+                column: 1,
+                text: "thread 'main' panicked at 'stack overflow'".to_string(),
+                expected: Expected::ResultLine,
+            })
+        );
+    }
+
+    #[test]
+    fn it_should_point_at_an_unexpected_header() {
+        let output = b"warning: unused variable: `x`\r\n";
+
+        let error = parse(output).unwrap_err();
+
+        assert_eq!((error.line, error.column), (1, 1));
+        assert_eq!(error.text, "warning: unused variable: `x`");
+        assert_eq!(error.expected, Expected::SuiteHeader);
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 1: expected a suite header (`Running ...` or `Doc-tests ...`), \
+             found `warning: unused variable: `x``"
+        );
+    }
+}
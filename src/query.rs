@@ -0,0 +1,245 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::iter::Filter;
+use std::slice;
+
+use suite::{Discrepancy, ModuleNode, Suite, Test};
+
+/// Counts summed over suites, as their result lines report them.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Totals {
+    pub suites: usize,
+    pub passed: i64,
+    pub failed: i64,
+    pub ignored: i64,
+    pub measured: i64,
+    pub total: i64,
+}
+
+/// The share of tests that ran and passed, leaving out ignored ones; `None`
+/// when none ran.
+fn pass_rate(passed: i64, failed: i64) -> Option<f64> {
+    match passed + failed {
+        0 => None,
+        run => Some(passed as f64 / run as f64),
+    }
+}
+
+impl Totals {
+    pub fn pass_rate(&self) -> Option<f64> {
+        pass_rate(self.passed, self.failed)
+    }
+}
+
+impl Suite {
+    pub fn pass_rate(&self) -> Option<f64> {
+        pass_rate(self.passed, self.failed)
+    }
+}
+
+impl<'a> ModuleNode<'a> {
+    pub fn pass_rate(&self) -> Option<f64> {
+        pass_rate(self.passed, self.failed)
+    }
+}
+
+/// Every test of some suites, with the suite it belongs to.
+pub struct Tests<'a> {
+    suites: slice::Iter<'a, Suite>,
+    current: Option<(&'a Suite, slice::Iter<'a, Test>)>,
+}
+
+impl<'a> Iterator for Tests<'a> {
+    type Item = (&'a Suite, &'a Test);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((suite, ref mut tests)) = self.current {
+                if let Some(test) = tests.next() {
+                    return Some((suite, test));
+                }
+            }
+            let suite = self.suites.next()?;
+            self.current = Some((suite, suite.tests.iter()));
+        }
+    }
+}
+
+/// Questions commonly asked of a whole run, answered for any slice of
+/// suites, such as what `cargo_test_result_parser` returns or
+/// `TestRun.suites`.
+pub trait Query {
+    fn totals(&self) -> Totals;
+
+    fn tests(&self) -> Tests<'_>;
+
+    /// The tests for which `predicate` holds, as in
+    /// `suites.tests_where(|&(suite, test)| suite.kind == SuiteKind::Doc && test.status == "fail")`.
+    fn tests_where<P>(&self, predicate: P) -> Filter<Tests<'_>, P>
+    where
+        P: FnMut(&(&Suite, &Test)) -> bool,
+    {
+        self.tests().filter(predicate)
+    }
+
+    /// Failed, timed out and crashed tests, in log order.
+    fn failed_tests(&self) -> Vec<(&Suite, &Test)>;
+
+    /// The `n` tests that took longest, slowest first. Tests without a
+    /// duration are left out.
+    fn slowest_tests(&self, n: usize) -> Vec<(&Suite, &Test)>;
+
+    /// The `n` suites that took longest, slowest first. Suites without a
+    /// duration are left out.
+    fn slowest_suites(&self, n: usize) -> Vec<&Suite>;
+
+    /// `Suite::validate` for every suite that has discrepancies.
+    fn discrepancies(&self) -> Vec<(&Suite, Vec<Discrepancy>)>;
+}
+
+impl Query for [Suite] {
+    fn totals(&self) -> Totals {
+        self.iter().fold(Totals::default(), |mut totals, suite| {
+            totals.suites += 1;
+            totals.passed += suite.passed;
+            totals.failed += suite.failed;
+            totals.ignored += suite.ignored;
+            totals.measured += suite.measured;
+            totals.total += suite.total;
+            totals
+        })
+    }
+
+    fn tests(&self) -> Tests<'_> {
+        Tests {
+            suites: self.iter(),
+            current: None,
+        }
+    }
+
+    fn failed_tests(&self) -> Vec<(&Suite, &Test)> {
+        self.tests_where(|&(_, test)| test.status.is_failure()).collect()
+    }
+
+    fn slowest_tests(&self, n: usize) -> Vec<(&Suite, &Test)> {
+        let mut tests: Vec<_> = self.tests_where(|&(_, test)| test.duration.is_some()).collect();
+
+        tests.sort_by(|a, b| b.1.duration.unwrap().total_cmp(&a.1.duration.unwrap()));
+        tests.truncate(n);
+        tests
+    }
+
+    fn slowest_suites(&self, n: usize) -> Vec<&Suite> {
+        let mut suites: Vec<&Suite> = self.iter().filter(|s| s.duration.is_some()).collect();
+
+        suites.sort_by(|a, b| b.duration.unwrap().total_cmp(&a.duration.unwrap()));
+        suites.truncate(n);
+        suites
+    }
+
+    fn discrepancies(&self) -> Vec<(&Suite, Vec<Discrepancy>)> {
+        self.iter()
+            .map(|suite| (suite, suite.validate()))
+            .filter(|(_, discrepancies)| !discrepancies.is_empty())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Query, Totals};
+    use suite::{Suite, SuiteKind, Test, TestStatus};
+
+    fn suites() -> Vec<Suite> {
+        let test = |name: &str, status: TestStatus, duration: f64| Test {
+            name: name.to_string(),
+            status,
+            duration: Some(duration),
+            ..Default::default()
+        };
+
+        vec![
+            Suite {
+                name: "api".to_string(),
+                passed: 2,
+                failed: 1,
+                total: 3,
+                tests: vec![
+                    test("routes::it_lists", TestStatus::Passed, 0.5),
+                    test("routes::it_creates", TestStatus::Failed, 2.0),
+                    test("routes::it_deletes", TestStatus::Passed, 0.25),
+                ],
+                duration: Some(2.75),
+                ..Default::default()
+            },
+            Suite {
+                name: "api_doc".to_string(),
+                kind: SuiteKind::Doc,
+                passed: 1,
+                ignored: 1,
+                total: 2,
+                tests: vec![
+                    test("src/lib.rs - Api (line 3)", TestStatus::Passed, 1.0),
+                    test("src/lib.rs - Api::new (line 9)", TestStatus::Ignored, 0.0),
+                ],
+                duration: Some(1.0),
+                ..Default::default()
+            },
+            Suite {
+                name: "empty".to_string(),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn it_should_total_all_suites() {
+        let totals = suites().totals();
+
+        assert_eq!(
+            totals,
+            Totals {
+                suites: 3,
+                passed: 3,
+                failed: 1,
+                ignored: 1,
+                measured: 0,
+                total: 5,
+            }
+        );
+        assert_eq!(totals.pass_rate(), Some(0.75));
+        assert_eq!(suites()[2].pass_rate(), None);
+    }
+
+    #[test]
+    fn it_should_find_tests_across_suites() {
+        let suites = suites();
+        let names = |tests: Vec<(&Suite, &Test)>| -> Vec<String> {
+            tests.iter().map(|&(s, t)| format!("{}/{}", s.name, t.name)).collect()
+        };
+
+        assert_eq!(suites.tests().count(), 5);
+        assert_eq!(names(suites.failed_tests()), vec!["api/routes::it_creates"]);
+        assert_eq!(
+            names(suites.slowest_tests(2)),
+            vec!["api/routes::it_creates", "api_doc/src/lib.rs - Api (line 3)"]
+        );
+        assert_eq!(
+            names(suites.tests_where(|&(s, _)| s.kind == SuiteKind::Doc).collect()),
+            vec!["api_doc/src/lib.rs - Api (line 3)", "api_doc/src/lib.rs - Api::new (line 9)"]
+        );
+        assert_eq!(
+            suites.slowest_suites(5).iter().map(|s| s.name.as_str()).collect::<Vec<_>>(),
+            vec!["api", "api_doc"]
+        );
+    }
+
+    #[test]
+    fn it_should_rate_modules() {
+        let suites = suites();
+        let tree = suites[0].module_tree();
+
+        assert_eq!(tree.find("routes").and_then(|m| m.pass_rate()), Some(2.0 / 3.0));
+    }
+}
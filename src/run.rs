@@ -0,0 +1,176 @@
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize};
+
+use suite::Suite;
+
+/// The version of the JSON representation of `TestRun`. It changes only
+/// when a field is renamed, removed or changes meaning; new fields may be
+/// added within a version, and readers fill them with their defaults.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Every suite of one parsed log.
+///
+/// With the `serde` feature this is the top-level JSON document:
+///
+/// ```json
+/// {
+///   "schema_version": 1,
+///   "suites": [
+///     {
+///       "name": "unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)",
+///       "state": "fail",
+///       "passed": 1,
+///       "failed": 1,
+///       "ignored": 0,
+///       "measured": 0,
+///       "total": 2,
+///       "tests": [
+///         {
+///           "name": "tests::it_fails",
+///           "status": "fail",
+///           "error": "thread 'tests::it_fails' panicked at src/lib.rs:12:9",
+///           "stdout": null,
+///           "lossy": false,
+///           "duration": 0.012
+///         }
+///       ],
+///       "anonymous_tests": false,
+///       "lossy": false,
+///       "package": "app",
+///       "crate_name": "app",
+///       "kind": "unit",
+///       "source": "src/lib.rs",
+///       "binary": "target/debug/deps/app-622eefdc86aa5319",
+///       "duration": 0.01,
+///       "unmatched_failures": []
+///     }
+///   ]
+/// }
+/// ```
+///
+/// `state` is `pass` or `fail`; `status` is one of `pass`, `fail`,
+/// `ignored`, `bench`, `timeout` and `crashed`; `kind` is one of `unit`,
+/// `integration`, `doc`, `bench`, `example` and `unknown`. Optional fields
+/// are `null` when unknown. `duration` is in seconds.
+#[derive(Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TestRun {
+    #[cfg_attr(feature = "serde", serde(deserialize_with = "schema_version"))]
+    pub schema_version: u32,
+    pub suites: Vec<Suite>,
+}
+
+/// Documents of another version may differ in any field, so they are
+/// rejected rather than read wrongly.
+#[cfg(feature = "serde")]
+fn schema_version<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u32, D::Error> {
+    let version = u32::deserialize(deserializer)?;
+
+    if version != SCHEMA_VERSION {
+        return Err(de::Error::custom(format!(
+            "unsupported schema_version {}, expected {}",
+            version, SCHEMA_VERSION
+        )));
+    }
+    Ok(version)
+}
+
+impl TestRun {
+    pub fn new(suites: Vec<Suite>) -> TestRun {
+        TestRun {
+            schema_version: SCHEMA_VERSION,
+            suites,
+        }
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod tests {
+    use serde_json;
+
+    use super::TestRun;
+    use annotation::{annotations, Annotation};
+    use query::{Query, Totals};
+    use suite::{Discrepancy, Suite, SuiteKind, SuiteState, Test, TestStatus};
+
+    fn run() -> TestRun {
+        TestRun::new(vec![Suite {
+            name: "unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)".to_string(),
+            state: SuiteState::Failed,
+            passed: 1,
+            failed: 1,
+            total: 2,
+            tests: vec![Test {
+                name: "tests::it_fails".to_string(),
+                status: TestStatus::Failed,
+                error: Some("thread 'tests::it_fails' panicked at src/lib.rs:12:9".to_string()),
+                duration: Some(0.012),
+                ..Default::default()
+            }],
+            package: Some("app".to_string()),
+            crate_name: Some("app".to_string()),
+            kind: SuiteKind::Unit,
+            source: Some("src/lib.rs".to_string()),
+            binary: Some("target/debug/deps/app-622eefdc86aa5319".to_string()),
+            duration: Some(0.01),
+            ..Default::default()
+        }])
+    }
+
+    #[test]
+    fn it_should_serialize_the_documented_representation() {
+        let documented = include_str!("run.rs")
+            .lines()
+            .skip_while(|l| !l.starts_with("/// ```json"))
+            .skip(1)
+            .take_while(|l| !l.starts_with("/// ```"))
+            .map(|l| l.trim_start_matches("///"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        assert_eq!(
+            serde_json::to_value(run()).unwrap(),
+            serde_json::from_str::<serde_json::Value>(&documented).unwrap()
+        );
+    }
+
+    #[test]
+    fn it_should_round_trip_report_types() {
+        let run = run();
+        let totals = run.suites.totals();
+        let discrepancies = run.suites[0].validate();
+        let annotations = annotations(&run.suites);
+
+        assert_eq!(serde_json::from_value::<TestRun>(serde_json::to_value(&run).unwrap()).unwrap(), run);
+        assert_eq!(serde_json::from_str::<Totals>(&serde_json::to_string(&totals).unwrap()).unwrap(), totals);
+        assert!(!discrepancies.is_empty());
+        assert_eq!(
+            serde_json::from_str::<Vec<Discrepancy>>(&serde_json::to_string(&discrepancies).unwrap()).unwrap(),
+            discrepancies
+        );
+        assert_eq!(serde_json::to_value(&annotations).unwrap()[0]["level"], "error");
+        assert_eq!(
+            serde_json::from_str::<Vec<Annotation>>(&serde_json::to_string(&annotations).unwrap()).unwrap(),
+            annotations
+        );
+    }
+
+    #[test]
+    fn it_should_fill_in_missing_fields() {
+        let json = r#"{"schema_version": 1, "suites": [{"name": "api", "state": "pass"}]}"#;
+
+        let run: TestRun = serde_json::from_str(json).unwrap();
+
+        assert_eq!(run.suites[0].name, "api");
+        assert_eq!(run.suites[0].kind, SuiteKind::Unknown);
+    }
+
+    #[test]
+    fn it_should_reject_other_schema_versions() {
+        let json = r#"{"schema_version": 2, "suites": []}"#;
+
+        let error = serde_json::from_str::<TestRun>(json).unwrap_err();
+
+        assert!(error.to_string().starts_with("unsupported schema_version 2, expected 1"));
+    }
+}
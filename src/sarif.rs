@@ -0,0 +1,356 @@
+use std::io::{self, Write};
+use std::ptr;
+
+use serde_json::{self, json, Value};
+
+use ansi::plain;
+use messages::{Diagnostic, DiagnosticSpan};
+use suite::{compile_error_location, Location, Suite, Test};
+use uri::{file_uri, is_absolute, relative_uri};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+const TEST_FAILURE: &str = "test-failure";
+const COMPILE_ERROR: &str = "compile-error";
+
+/// Relative paths stay relative to the checkout; absolute ones, of
+/// dependencies and the standard library, become `file://` URIs.
+fn uri(file: &str) -> String {
+    if is_absolute(file) {
+        file_uri(file)
+    } else {
+        relative_uri(file)
+    }
+}
+
+fn physical_location(file: &str, region: Value) -> Value {
+    json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": uri(file) },
+            "region": region,
+        }
+    })
+}
+
+fn panic_location(location: &Location) -> Value {
+    let mut region = json!({ "startLine": location.line });
+    if let Some(column) = location.column {
+        region["startColumn"] = json!(column);
+    }
+    physical_location(location.file, region)
+}
+
+fn span_location(span: &DiagnosticSpan) -> Value {
+    physical_location(
+        &span.file_name,
+        json!({
+            "startLine": span.line_start,
+            "startColumn": span.column_start,
+            "endLine": span.line_end,
+            "endColumn": span.column_end,
+        }),
+    )
+}
+
+/// Error codes link to the error index, Clippy lints to the lint list.
+fn rule(id: &str) -> Value {
+    let help = if id.starts_with('E') && id[1..].chars().all(|c| c.is_ascii_digit()) {
+        Some(format!("https://doc.rust-lang.org/error_codes/{}.html", id))
+    } else {
+        id.strip_prefix("clippy::")
+            .map(|lint| format!("https://rust-lang.github.io/rust-clippy/master/index.html#{}", lint))
+    };
+    let description = match id {
+        TEST_FAILURE => "A test failed, timed out or crashed",
+        COMPILE_ERROR => "The tests did not compile",
+        _ => id,
+    };
+
+    let mut rule = json!({ "id": id, "shortDescription": { "text": description } });
+    if let Some(help) = help {
+        rule["helpUri"] = json!(help);
+    }
+    rule
+}
+
+/// The results of a run and the rules they refer to, by index, in the
+/// order rules first appear.
+#[derive(Default)]
+struct Log {
+    rules: Vec<String>,
+    results: Vec<Value>,
+}
+
+impl Log {
+    fn push(&mut self, rule_id: &str, level: &str, message: &str, mut result: Value) {
+        let index = match self.rules.iter().position(|r| r == rule_id) {
+            Some(index) => index,
+            None => {
+                self.rules.push(rule_id.to_string());
+                self.rules.len() - 1
+            }
+        };
+
+        result["ruleId"] = json!(rule_id);
+        result["ruleIndex"] = json!(index);
+        result["level"] = json!(level);
+        result["message"] = json!({ "text": message });
+        self.results.push(result);
+    }
+
+    /// Diagnostics without spans, such as `aborting due to 2 previous
+    /// errors`, only sum up the others and are left out, as are notes.
+    fn diagnostic(&mut self, diagnostic: &Diagnostic) {
+        let level = match diagnostic.level.as_str() {
+            "warning" => "warning",
+            level if level.starts_with("error") => "error",
+            _ => return,
+        };
+        let primary = match diagnostic.spans.iter().find(|s| s.is_primary).or_else(|| diagnostic.spans.first()) {
+            Some(span) => span,
+            None => return,
+        };
+        let related: Vec<Value> = diagnostic
+            .spans
+            .iter()
+            .filter(|s| !ptr::eq(*s, primary))
+            .enumerate()
+            .map(|(i, span)| {
+                let mut location = span_location(span);
+                location["id"] = json!(i);
+                if let Some(ref label) = span.label {
+                    location["message"] = json!({ "text": label });
+                }
+                location
+            })
+            .collect();
+
+        let mut result = json!({ "locations": [span_location(primary)] });
+        if !related.is_empty() {
+            result["relatedLocations"] = json!(related);
+        }
+        let rule_id = diagnostic.code.as_ref().map_or(COMPILE_ERROR, |c| c.as_str());
+        self.push(rule_id, level, &diagnostic.message, result);
+    }
+
+    fn test(&mut self, suite: &Suite, test: &Test) {
+        if !test.status.is_failure() {
+            return;
+        }
+
+        let mut result = json!({
+            "logicalLocations": [{
+                "fullyQualifiedName": suite.test_id(test).to_string(),
+                "kind": "function",
+            }],
+        });
+
+        if test.is_compile_error() {
+            let error = test.error.as_ref().map_or("", |e| e.as_str());
+            if let Some(location) = compile_error_location(error) {
+                result["locations"] = json!([panic_location(&location)]);
+            }
+            self.push(COMPILE_ERROR, "error", &plain(error), result);
+        } else {
+            if let Some(location) = test.panic_location() {
+                result["locations"] = json!([panic_location(&location)]);
+            }
+            let message = match test.panic_message() {
+                Some(message) => plain(message),
+                None => format!("{} {}", test.name, test.status),
+            };
+            self.push(TEST_FAILURE, "error", &message, result);
+        }
+    }
+}
+
+/// Writes a SARIF 2.1.0 log for code scanning. Compiler errors and warnings
+/// become results at their primary span, with the other spans as related
+/// locations, under their error code or lint as the rule; failed tests
+/// become `test-failure` results where they panicked.
+///
+/// Pass `JsonRun::diagnostics()` for runs with `--message-format=json`.
+/// When they hold errors, the `compile failed` tests read from plain output
+/// are left out, as the diagnostics cover them.
+pub fn sarif_writer<'a, W, D>(suites: &[Suite], diagnostics: D, output: W) -> io::Result<()>
+where
+    W: Write,
+    D: IntoIterator<Item = &'a Diagnostic>,
+{
+    let mut log = Log::default();
+
+    for diagnostic in diagnostics {
+        log.diagnostic(diagnostic);
+    }
+    let has_errors = log.results.iter().any(|r| r["level"] == "error");
+    for suite in suites {
+        for test in &suite.tests {
+            if !(has_errors && test.is_compile_error()) {
+                log.test(suite, test);
+            }
+        }
+    }
+
+    let rules: Vec<Value> = log.rules.iter().map(|r| rule(r)).collect();
+    let sarif = json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                }
+            },
+            "results": log.results,
+        }]
+    });
+
+    serde_json::to_writer_pretty(output, &sarif).map_err(io::Error::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::{self, json, Value};
+
+    use super::sarif_writer;
+    use fixtures::suites;
+    use messages::{Diagnostic, DiagnosticSpan};
+    use suite::Suite;
+
+    fn sarif(suites: &[Suite], diagnostics: &[Diagnostic]) -> Value {
+        let mut output = vec![];
+        sarif_writer(suites, diagnostics, &mut output).unwrap();
+        serde_json::from_slice(&output).unwrap()
+    }
+
+    #[test]
+    fn it_should_report_failed_tests_where_they_panicked() {
+        let sarif = sarif(&suites(), &[]);
+        let run = &sarif["runs"][0];
+
+        assert_eq!(sarif["version"], "2.1.0");
+        assert_eq!(run["tool"]["driver"]["rules"][0]["id"], "test-failure");
+        assert_eq!(
+            run["results"][0],
+            json!({
+                "ruleId": "test-failure",
+                "ruleIndex": 0,
+                "level": "error",
+                "message": { "text": "100% `a < b`\n```" },
+                "locations": [{
+                    "physicalLocation": {
+                        "artifactLocation": { "uri": "src/lib.rs" },
+                        "region": { "startLine": 12, "startColumn": 9 },
+                    }
+                }],
+                "logicalLocations": [{
+                    "fullyQualifiedName": "unit/app::tests::it_fails",
+                    "kind": "function",
+                }],
+            })
+        );
+        assert_eq!(run["results"][1]["message"]["text"], "tests::it_hangs timeout");
+        assert_eq!(run["results"][1].get("locations"), None);
+        assert_eq!(run["results"][2]["ruleId"], "compile-error");
+        assert_eq!(
+            run["results"][2]["locations"][0]["physicalLocation"]["artifactLocation"]["uri"],
+            "src/main.rs"
+        );
+    }
+
+    #[test]
+    fn it_should_report_diagnostics_with_related_locations() {
+        let span = |line, is_primary, label: Option<&str>| DiagnosticSpan {
+            file_name: "src/main.rs".to_string(),
+            line_start: line,
+            line_end: line,
+            column_start: 5,
+            column_end: 15,
+            is_primary,
+            label: label.map(|l| l.to_string()),
+        };
+        let diagnostics = vec![
+            Diagnostic {
+                level: "error".to_string(),
+                message: "mismatched types".to_string(),
+                code: Some("E0308".to_string()),
+                spans: vec![span(1, false, Some("expected due to this")), span(3, true, None)],
+                rendered: None,
+            },
+            Diagnostic {
+                level: "error".to_string(),
+                message: "aborting due to 1 previous error".to_string(),
+                code: None,
+                spans: vec![],
+                rendered: None,
+            },
+        ];
+
+        let sarif = sarif(&suites(), &diagnostics);
+        let run = &sarif["runs"][0];
+
+        assert_eq!(run["results"].as_array().unwrap().len(), 3);
+        assert!(run["results"].as_array().unwrap().iter().all(|r| r["ruleId"] != "compile-error"));
+        assert_eq!(
+            run["tool"]["driver"]["rules"][0]["helpUri"],
+            "https://doc.rust-lang.org/error_codes/E0308.html"
+        );
+        assert_eq!(run["results"][0]["locations"][0]["physicalLocation"]["region"]["startLine"], 3);
+        assert_eq!(
+            run["results"][0]["relatedLocations"],
+            json!([{
+                "id": 0,
+                "message": { "text": "expected due to this" },
+                "physicalLocation": {
+                    "artifactLocation": { "uri": "src/main.rs" },
+                    "region": { "startLine": 1, "startColumn": 5, "endLine": 1, "endColumn": 15 },
+                }
+            }])
+        );
+    }
+
+    #[test]
+    fn it_should_write_file_uris_for_absolute_paths() {
+        let diagnostic = |file: &str| Diagnostic {
+            level: "warning".to_string(),
+            message: "unused variable: `x`".to_string(),
+            code: None,
+            spans: vec![DiagnosticSpan {
+                file_name: file.to_string(),
+                line_start: 1,
+                line_end: 1,
+                column_start: 5,
+                column_end: 6,
+                is_primary: true,
+                label: None,
+            }],
+            rendered: None,
+        };
+        let diagnostics = vec![
+            diagnostic("src/100% (#1)/ünï.rs"),
+            diagnostic("/rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/core/src/option.rs"),
+            diagnostic("/home/u/.cargo/registry/src/serde-1.0.0/src/de.rs"),
+            diagnostic("C:\\Users\\u\\src\\de.rs"),
+        ];
+
+        let sarif = sarif(&[], &diagnostics);
+        let uris: Vec<&Value> = sarif["runs"][0]["results"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| &r["locations"][0]["physicalLocation"]["artifactLocation"]["uri"])
+            .collect();
+
+        assert_eq!(
+            uris,
+            vec![
+                "src/100%25%20%28%231%29/%C3%BCn%C3%AF.rs",
+                "file:///rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/core/src/option.rs",
+                "file:///home/u/.cargo/registry/src/serde-1.0.0/src/de.rs",
+                "file:///C:/Users/u/src/de.rs",
+            ]
+        );
+    }
+}
@@ -1,66 +1,189 @@
-use std::str;
-use nom::line_ending;
+use std::borrow::Cow;
+use nom::{ErrorKind, IResult};
+
+use utility_parsers::{decode, map_text, skip_blank_lines, split_line};
+
+const STDOUT_MARKER: &str = " stdout ----";
 
 #[derive(Debug, PartialEq)]
 pub struct Failure<'a, 'b> {
-    pub name: &'a str,
-    pub error: &'b str,
-}
-
-named!(
-    fail_line<&str>,
-    do_parse!(
-        ws!(tag!("----")) >>
-        name: map_res!(
-            take_until!(" "),
-            str::from_utf8
-        ) >>
-        ws!(tag!("stdout")) >>
-        ws!(tag!("----")) >>
-        (name)
-    )
-);
-
-named!(
-    failure<Failure>,
-    do_parse!(
-        name: fail_line >>
-        error: map_res!(take_until!("\n"), str::from_utf8) >>
-        opt!(
-            tag!("note: Run with `RUST_BACKTRACE=1` for a backtrace.")
-        ) >>
-        line_ending >>
-        line_ending >>
-        (Failure {
-            name:name,
-            error:error
-        })
-    )
-);
-
-named!(failures<Vec<Failure> >, many1!(failure));
-
-named!(pub fail_opt<Option<Vec<Failure> > >,
-    opt!(
-        do_parse!(
-            ws!(
-                tag!("failures:")
-            ) >>
-            f: failures >>
-            take_until!(
-                "test result: "
-            ) >>
-            (f)
-        )
+    pub name: Cow<'a, str>,
+    pub error: Cow<'b, str>,
+    /// The name or output had bytes that were not valid UTF-8.
+    pub lossy: bool,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct FailureSection<'a> {
+    pub failures: Vec<Failure<'a, 'a>>,
+    /// The names listed after the output, each with whether it had to be
+    /// decoded lossily.
+    pub names: Vec<(Cow<'a, str>, bool)>,
+}
+
+fn stdout_name(line: &str) -> &str {
+    let line = line.trim_end();
+
+    line.strip_suffix(STDOUT_MARKER).unwrap_or(line)
+}
+
+fn failure_name(line: Cow<str>) -> Option<Cow<str>> {
+    if line.trim_end().ends_with(STDOUT_MARKER) {
+        Some(map_text(line, stdout_name))
+    } else {
+        None
+    }
+}
+
+fn is_fail_line(line: &[u8]) -> bool {
+    line.trim_ascii_start().starts_with(b"---- ")
+        && line.trim_ascii_end().ends_with(STDOUT_MARKER.as_bytes())
+}
+
+fn is_failures_header(line: &[u8]) -> bool {
+    line.trim_ascii() == b"failures:"
+}
+
+fn is_result_line(line: &[u8]) -> bool {
+    line.trim_ascii_start().starts_with(b"test result: ")
+}
+
+fn is_indented_name(line: &[u8]) -> bool {
+    (line.starts_with(b" ") || line.starts_with(b"\t")) && !line.trim_ascii().is_empty()
+}
+
+fn no_match<'a, T>() -> IResult<&'a [u8], T> {
+    IResult::Error(ErrorKind::Tag)
+}
+
+pub fn fail_line(input: &[u8]) -> IResult<&[u8], (Cow<'_, str>, bool)> {
+    let name = split_line(skip_blank_lines(input)).and_then(|(line, rest)| {
+        let (name, lossy) = decode(line.trim_ascii_start().strip_prefix(b"---- ")?);
+
+        Some((rest, (failure_name(name)?, lossy)))
+    });
+
+    match name {
+        Some((rest, name)) => IResult::Done(skip_blank_lines(rest), name),
+        None => no_match(),
+    }
+}
+
+fn is_backtrace_note(line: &str) -> bool {
+    let line = line.trim();
+
+    line.starts_with("note:") && line.contains("RUST_BACKTRACE")
+}
+
+fn failure_text(body: &str) -> &str {
+    let body = body.trim();
+
+    match body.rfind('\n') {
+        Some(i) if is_backtrace_note(&body[i + 1..]) => body[..i].trim_end(),
+        None if is_backtrace_note(body) => "",
+        _ => body,
+    }
+}
+
+/// The output of a failed test runs until the next test's output, the list
+/// of failed tests or the result line.
+pub fn failure(input: &[u8]) -> IResult<&[u8], Failure<'_, '_>> {
+    let (body, (name, name_lossy)) = match fail_line(input) {
+        IResult::Done(rest, name) => (rest, name),
+        _ => return no_match(),
+    };
+    let mut rest = body;
+
+    while let Some((line, next)) = split_line(rest) {
+        if is_fail_line(line) || is_failures_header(line) || is_result_line(line) {
+            break;
+        }
+        rest = next;
+    }
+
+    let (error, error_lossy) = decode(&body[..body.len() - rest.len()]);
+
+    IResult::Done(
+        rest,
+        Failure {
+            name,
+            error: map_text(error, failure_text),
+            lossy: name_lossy || error_lossy,
+        },
     )
-);
+}
+
+pub fn failures(mut input: &[u8]) -> IResult<&[u8], Vec<Failure<'_, '_>>> {
+    let mut failures = vec![];
+
+    while let IResult::Done(rest, failure) = failure(input) {
+        failures.push(failure);
+        input = rest;
+    }
+
+    if failures.is_empty() {
+        no_match()
+    } else {
+        IResult::Done(input, failures)
+    }
+}
+
+/// The indented names under the second `failures:` header.
+fn failure_names(input: &[u8]) -> IResult<&[u8], Vec<(Cow<'_, str>, bool)>> {
+    let mut rest = match split_line(skip_blank_lines(input)) {
+        Some((line, rest)) if is_failures_header(line) => rest,
+        _ => return no_match(),
+    };
+    let mut names = vec![];
+
+    while let Some((line, next)) = split_line(rest) {
+        if !is_indented_name(line) {
+            break;
+        }
+        let (name, lossy) = decode(line);
+        names.push((map_text(name, str::trim), lossy));
+        rest = next;
+    }
+
+    IResult::Done(rest, names)
+}
+
+/// The failure section, if the suite has one, up to the result line. Its end
+/// is not known until the result line is found, hence `Incomplete` without
+/// one. Every line of the section is looked at once, whatever follows it, so
+/// huge sections are read in linear time.
+pub fn fail_opt(input: &[u8]) -> IResult<&[u8], Option<FailureSection<'_>>> {
+    let mut rest = match split_line(skip_blank_lines(input)) {
+        Some((line, rest)) if is_failures_header(line) => rest,
+        _ => return IResult::Done(input, None),
+    };
+    let mut section = FailureSection::default();
+
+    if let IResult::Done(next, failures) = failures(rest) {
+        section.failures = failures;
+        rest = next;
+    }
+    if let IResult::Done(next, names) = failure_names(rest) {
+        section.names = names;
+        rest = next;
+    }
+
+    while let Some((line, next)) = split_line(rest) {
+        if is_result_line(line) {
+            return IResult::Done(rest, Some(section));
+        }
+        rest = next;
+    }
+
+    IResult::Incomplete(::nom::Needed::Unknown)
+}
 
 #[cfg(test)]
 mod tests {
     use nom::IResult;
     use std::fmt::Debug;
 
-    use super::{fail_line, failure, Failure, failures, fail_opt};
+    use super::{fail_line, failure, Failure, FailureSection, failures, fail_opt};
 
     fn assert_left<R: PartialEq + Debug>(l: IResult<&[u8], R>, r: R, remaining: &[u8]) {
         assert_eq!(
@@ -80,7 +203,7 @@ mod tests {
     fn test_fail_line() {
         let output = b"---- fail stdout ----";
 
-        assert_done(fail_line(output), "fail");
+        assert_done(fail_line(output), ("fail".into(), false));
     }
 
     #[test]
@@ -93,9 +216,33 @@ note: Run with `RUST_BACKTRACE=1` for a backtrace.
         assert_done(
             failure(output),
             Failure {
-                name: "fail",
+                name: "fail".into(),
                 error: "thread 'fail' panicked at 'assertion failed: `(left == right)` \
-                        (left: `1`, right: `2`)', tests/integration_test.rs:16",
+                        (left: `1`, right: `2`)', tests/integration_test.rs:16".into(),
+                lossy: false,
+            },
+        );
+    }
+
+    #[test]
+    fn test_failure_with_crlf() {
+        let output = b"---- tests::it_adds stdout ----\r
+thread 'tests::it_adds' panicked at src/lib.rs:12:9:\r
+assertion `left == right` failed\r
+  left: 1\r
+ right: 2\r
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace\r
+\r
+";
+        assert_done(
+            failure(output),
+            Failure {
+                name: "tests::it_adds".into(),
+                error: "thread 'tests::it_adds' panicked at src/lib.rs:12:9:\r
+assertion `left == right` failed\r
+  left: 1\r
+ right: 2".into(),
+                lossy: false,
             },
         );
     }
@@ -116,12 +263,14 @@ note: Run with `RUST_BACKTRACE=1` for a backtrace.
             failures(output),
             vec![
                 Failure {
-                    name: "fail",
-                    error: "thread 'fail' panicked at 'assertion failed: `(left == right)` (left: `1`, right: `2`)', tests/integration_test.rs:16"
+                    name: "fail".into(),
+                    error: "thread 'fail' panicked at 'assertion failed: `(left == right)` (left: `1`, right: `2`)', tests/integration_test.rs:16".into(),
+                    lossy: false,
                 },
                 Failure {
-                    name: "fail2",
-                    error: "thread 'fail2' panicked at 'assertion failed: `(left == right)` (left: `3`, right: `2`)', tests/integration_test.rs:22"
+                    name: "fail2".into(),
+                    error: "thread 'fail2' panicked at 'assertion failed: `(left == right)` (left: `3`, right: `2`)', tests/integration_test.rs:22".into(),
+                    lossy: false,
                 }
             ],
         );
@@ -147,17 +296,61 @@ test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out;
 "[..];
 
         assert_left(fail_opt(output),
-                    Some(vec![
-                        Failure {
-                            name: "fail",
-                            error: "thread 'fail' panicked at 'assertion failed: `(left == right)` (left: `1`, right: `2`)', tests/integration_test.rs:16",
-                        },
-                        Failure {
-                            name: "fail2",
-                            error: "thread 'fail2' panicked at 'assertion failed: `(left == right)` (left: `3`, right: `2`)', tests/integration_test.rs:22",
-                        },
-                    ]),
+                    Some(FailureSection {
+                        failures: vec![
+                            Failure {
+                                name: "fail".into(),
+                                error: "thread 'fail' panicked at 'assertion failed: `(left == right)` (left: `1`, right: `2`)', tests/integration_test.rs:16".into(),
+                                lossy: false,
+                            },
+                            Failure {
+                                name: "fail2".into(),
+                                error: "thread 'fail2' panicked at 'assertion failed: `(left == right)` (left: `3`, right: `2`)', tests/integration_test.rs:22".into(),
+                                lossy: false,
+                            },
+                        ],
+                        names: vec![("fail".into(), false), ("fail2".into(), false)],
+                    }),
         &b"test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
 "[..]);
     }
+
+    #[test]
+    fn test_fail_opt_without_output() {
+        let output = &b"failures:
+
+failures:
+    tests::it_times_out
+
+test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+"[..];
+
+        assert_left(fail_opt(output),
+                    Some(FailureSection {
+                        failures: vec![],
+                        names: vec![("tests::it_times_out".into(), false)],
+                    }),
+        &b"test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+"[..]);
+    }
+
+    #[test]
+    fn test_fail_opt_with_long_blank_runs() {
+        let mut output = b"failures:\n\n---- fail stdout ----\nthread 'fail' panicked\n".to_vec();
+        output.extend(b"  \n".repeat(200_000));
+        output.extend(b"failures:\n    fail\n\ntest result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out\n");
+
+        assert_left(fail_opt(&output),
+                    Some(FailureSection {
+                        failures: vec![
+                            Failure {
+                                name: "fail".into(),
+                                error: "thread 'fail' panicked".into(),
+                                lossy: false,
+                            },
+                        ],
+                        names: vec![("fail".into(), false)],
+                    }),
+        &b"test result: FAILED. 0 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out\n"[..]);
+    }
 }
@@ -0,0 +1,142 @@
+use std::fmt;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use suite::{crate_name, Suite, SuiteKind, Test};
+
+/// Names a test the same way across builds and machines: unlike
+/// `Suite.name`, it holds no hash, target directory or profile.
+///
+/// Displayed as `package/kind/target@source::path`, as in
+/// `api-server/unit/api_server@src/main.rs::routes::tests::it_works`, without
+/// the `package/` or `@source` when they are unknown.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TestId {
+    pub package: Option<String>,
+    pub kind: SuiteKind,
+    /// The crate under test, such as `api_server` or `routes` for
+    /// `tests/routes.rs`.
+    pub target: String,
+    /// The target's source file, with `/` as the path separator. It tells
+    /// apart the unit tests of a library and a binary of the same name.
+    pub source: Option<String>,
+    /// The test's name, with `/` as the path separator in doc test names.
+    pub path: String,
+}
+
+impl TestId {
+    pub fn new(suite: &Suite, test: &Test) -> TestId {
+        let target = match suite.crate_name {
+            Some(ref name) => name.clone(),
+            None => crate_name(&suite.name).into_owned(),
+        };
+
+        TestId {
+            package: suite.package.clone(),
+            kind: suite.kind,
+            target,
+            source: suite.source.as_ref().map(|s| s.replace('\\', "/")),
+            path: test.name.replace('\\', "/"),
+        }
+    }
+}
+
+impl fmt::Display for TestId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(ref package) = self.package {
+            write!(f, "{}/", package)?;
+        }
+        write!(f, "{}/{}", self.kind.as_str(), self.target)?;
+        if let Some(ref source) = self.source {
+            write!(f, "@{}", source)?;
+        }
+        write!(f, "::{}", self.path)
+    }
+}
+
+impl Suite {
+    pub fn test_id(&self, test: &Test) -> TestId {
+        TestId::new(self, test)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use parse;
+    use suite::{crate_name, Suite, SuiteKind, Test};
+
+    fn suite(name: &str, kind: SuiteKind, source: Option<&str>) -> Suite {
+        Suite {
+            name: name.to_string(),
+            crate_name: Some(crate_name(name).into_owned()),
+            package: Some("api-server".to_string()),
+            kind,
+            source: source.map(|s| s.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn test(name: &str) -> Test {
+        Test {
+            name: name.to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_should_ignore_hashes_directories_and_platforms() {
+        let linux = suite(
+            "unittests src/lib.rs (target/debug/deps/api_server-622eefdc86aa5319)",
+            SuiteKind::Unit,
+            Some("src/lib.rs"),
+        );
+        let windows = suite(
+            "unittests src\\lib.rs (target\\release\\deps\\api_server-0b1c2d3e4f5a6b7c.exe)",
+            SuiteKind::Unit,
+            Some("src\\lib.rs"),
+        );
+        let test = test("routes::tests::it_works");
+
+        assert_eq!(linux.test_id(&test), windows.test_id(&test));
+        assert_eq!(
+            linux.test_id(&test).to_string(),
+            "api-server/unit/api_server@src/lib.rs::routes::tests::it_works"
+        );
+    }
+
+    #[test]
+    fn it_should_tell_library_and_binary_apart() {
+        let output = b"     Running unittests src/lib.rs (target/debug/deps/app-1111111111111111)
+
+running 1 test
+test tests::it_works ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+     Running unittests src/main.rs (target/debug/deps/app-2222222222222222)
+
+running 1 test
+test tests::it_works ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+        let suites = parse(output).unwrap().suites;
+        let ids: Vec<String> = suites.iter().map(|s| s.test_id(&s.tests[0]).to_string()).collect();
+
+        assert_eq!(
+            ids,
+            vec!["unit/app@src/lib.rs::tests::it_works", "unit/app@src/main.rs::tests::it_works"]
+        );
+    }
+
+    #[test]
+    fn it_should_normalize_doc_test_paths() {
+        let doc = suite("api_server", SuiteKind::Doc, None);
+
+        assert_eq!(
+            doc.test_id(&test("src\\routes.rs - routes::Route (line 12)")).path,
+            "src/routes.rs - routes::Route (line 12)"
+        );
+    }
+}
@@ -0,0 +1,132 @@
+use std::borrow::Cow;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use suite::binary_path;
+use utility_parsers::map_text;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(rename_all = "snake_case"))]
+pub enum SuiteKind {
+    /// Tests inside a library or binary, `Running unittests src/lib.rs (...)`.
+    Unit,
+    /// A target under `tests/`.
+    Integration,
+    /// `Doc-tests foo`.
+    Doc,
+    /// A target under `benches/`.
+    Bench,
+    /// A target under `examples/`.
+    Example,
+    /// Cargo before 1.52 printed only the binary, `Running target/debug/foo-<hash>`.
+    #[default]
+    Unknown,
+}
+
+impl SuiteKind {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            SuiteKind::Unit => "unit",
+            SuiteKind::Integration => "integration",
+            SuiteKind::Doc => "doc",
+            SuiteKind::Bench => "bench",
+            SuiteKind::Example => "example",
+            SuiteKind::Unknown => "unknown",
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SuiteLine<'a> {
+    pub name: Cow<'a, str>,
+    pub kind: SuiteKind,
+    pub source: Option<Cow<'a, str>>,
+    pub binary: Option<Cow<'a, str>>,
+    pub lossy: bool,
+}
+
+fn target_kind(source: &str) -> SuiteKind {
+    match source.split(['/', '\\']).next() {
+        Some("benches") => SuiteKind::Bench,
+        Some("examples") => SuiteKind::Example,
+        _ => SuiteKind::Integration,
+    }
+}
+
+/// The source file of a `Running` line that names one in front of the binary.
+fn source_path(name: &str) -> &str {
+    let target = name[..name.len() - binary_path(name).len() - 2].trim_end();
+
+    target.strip_prefix("unittests ").unwrap_or(target)
+}
+
+/// Splits what follows `Running` into the source file and the binary, as in
+/// `unittests src/lib.rs (target/debug/deps/foo-<hash>)` or
+/// `tests/api.rs (target/debug/deps/api-<hash>)`.
+pub fn running_line(name: Cow<str>, lossy: bool) -> SuiteLine {
+    let binary = map_text(name.clone(), binary_path);
+
+    if binary.len() == name.len() {
+        return SuiteLine {
+            name,
+            kind: SuiteKind::Unknown,
+            source: None,
+            binary: Some(binary),
+            lossy,
+        };
+    }
+
+    let source = map_text(name.clone(), source_path);
+    let kind = if name.starts_with("unittests ") {
+        SuiteKind::Unit
+    } else {
+        target_kind(&source)
+    };
+
+    SuiteLine {
+        kind,
+        source: Some(source),
+        binary: Some(binary),
+        name,
+        lossy,
+    }
+}
+
+pub fn doc_tests_line(name: Cow<str>, lossy: bool) -> SuiteLine {
+    SuiteLine {
+        name,
+        kind: SuiteKind::Doc,
+        source: None,
+        binary: None,
+        lossy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{running_line, SuiteKind, SuiteLine};
+
+    #[test]
+    fn it_should_split_a_unit_test_line() {
+        assert_eq!(
+            running_line("unittests src/main.rs (target/debug/deps/app-622eefdc86aa5319)".into(), false),
+            SuiteLine {
+                name: "unittests src/main.rs (target/debug/deps/app-622eefdc86aa5319)".into(),
+                kind: SuiteKind::Unit,
+                source: Some("src/main.rs".into()),
+                binary: Some("target/debug/deps/app-622eefdc86aa5319".into()),
+                lossy: false,
+            }
+        );
+    }
+
+    #[test]
+    fn it_should_classify_targets_by_directory() {
+        let kind = |line: &str| running_line(line.into(), false).kind;
+
+        assert_eq!(kind("tests/api.rs (target/debug/deps/api-0b1c2d3e4f5a6b7c)"), SuiteKind::Integration);
+        assert_eq!(kind("benches/parse.rs (target/release/deps/parse-1a2b3c4d)"), SuiteKind::Bench);
+        assert_eq!(kind("examples/demo.rs (target/debug/examples/demo-8b7a6f5e)"), SuiteKind::Example);
+        assert_eq!(kind("target/debug/deps/app-622eefdc86aa5319"), SuiteKind::Unknown);
+    }
+}
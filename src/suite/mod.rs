@@ -1,105 +1,337 @@
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
 use std::str;
-use nom::space;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use nom::{digit, line_ending, multispace, not_line_ending, space, ErrorKind, IResult};
 
-use utility_parsers::{ok_or_failed, rest_of_line};
+use borrowed;
+use parse::Expected;
+use utility_parsers::{decode, decoded_line, map_text, rest_of_line, test_status};
 
 mod result_line;
 mod failure;
+mod id;
+mod kind;
+mod package;
+mod panic;
+mod status;
+mod tree;
+mod validate;
 
-use self::result_line::{SuiteResult, suite_result};
-use self::failure::{fail_opt, Failure};
+use self::result_line::{result_line, SuiteResult};
+use self::failure::{fail_opt, Failure, FailureSection};
+use self::kind::{doc_tests_line, running_line, SuiteLine};
+pub use self::id::TestId;
+pub use self::kind::SuiteKind;
+pub use self::package::{assign_packages, binary_path, crate_name, group_by_package, suite_crate_name};
+pub use self::panic::{compile_error_location, Location};
+pub use self::status::{SuiteState, TestStatus};
+pub use self::tree::ModuleNode;
+pub use self::validate::Discrepancy;
 
-#[derive(Debug, PartialEq)]
+/// The name of the test a compile error is reported as.
+pub const COMPILE_FAILED: &str = "compile failed";
+
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
 pub struct Test {
     pub name: String,
-    pub status: String,
+    pub status: TestStatus,
     pub error: Option<String>,
+    pub stdout: Option<String>,
+    /// The name or error contained bytes that were not valid UTF-8 and have
+    /// been replaced with U+FFFD.
+    pub lossy: bool,
+    /// Seconds the test took, when the log says: libtest's `--report-time`
+    /// or a JUnit `time` attribute.
+    pub duration: Option<f64>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(default))]
 pub struct Suite {
     pub name: String,
-    pub state: String,
+    pub state: SuiteState,
     pub passed: i64,
     pub failed: i64,
     pub ignored: i64,
     pub measured: i64,
     pub total: i64,
     pub tests: Vec<Test>,
+    /// Set for quiet (`-q`) or `--format terse` runs, where only failed tests
+    /// are named and `tests` holds just those.
+    pub anonymous_tests: bool,
+    /// The suite name or any of its tests had to be decoded lossily.
+    pub lossy: bool,
+    /// The workspace package the suite was built from, when the log shows it.
+    pub package: Option<String>,
+    /// The crate under test, such as `my_lib` for `target/debug/deps/my_lib-<hash>`.
+    pub crate_name: Option<String>,
+    pub kind: SuiteKind,
+    /// The target's source file, when cargo printed it, e.g. `tests/api.rs`.
+    pub source: Option<String>,
+    /// The test executable that was run; doc tests have none.
+    pub binary: Option<String>,
+    /// Seconds the suite ran, from libtest's `finished in` or a JUnit `time`.
+    pub duration: Option<f64>,
+    /// Names of captured failure output that matched no test of the suite,
+    /// as when an interleaved print swallowed the test's result line.
+    pub unmatched_failures: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct TerseMarks {
+    passed: i64,
+    failed: i64,
+    ignored: i64,
 }
 
-fn find_message_by_name(name: &str, failures: &Vec<Failure>) -> Option<String> {
-    failures.iter().find(|x| x.name == name).map(|x| x.error.to_string())
+/// The captured output of each failed test, with whether it had to be
+/// decoded lossily. Names repeat only in malformed logs; the first wins.
+type FailureIndex<'a> = HashMap<Cow<'a, str>, (Cow<'a, str>, bool)>;
+
+fn failure_message(error: Cow<str>) -> Cow<str> {
+    if error.contains("\r\n") {
+        Cow::Owned(error.replace("\r\n", "\n"))
+    } else {
+        error
+    }
 }
 
-fn handle_parsed_suite(
-    name: String,
-    tests: Vec<Test>,
-    failures: Option<Vec<Failure>>,
+fn index_failures<'a>(failures: Vec<Failure<'a, 'a>>) -> FailureIndex<'a> {
+    let mut index = HashMap::with_capacity(failures.len());
+
+    for Failure { name, error, lossy } in failures {
+        index.entry(name).or_insert_with(|| (failure_message(error), lossy));
+    }
+
+    index
+}
+
+/// Failures whose name matches none of `names`, in log order.
+fn unmatched_failures<'a, 'n, I>(failures: &[Failure<'a, 'a>], names: I) -> Vec<Cow<'a, str>>
+where
+    I: Iterator<Item = &'n str>,
+{
+    let names: HashSet<&str> = names.collect();
+
+    failures
+        .iter()
+        .filter(|f| !names.contains(&*f.name))
+        .map(|f| f.name.clone())
+        .collect()
+}
+
+fn count_marks(lines: Vec<&[u8]>) -> TerseMarks {
+    lines.iter().flat_map(|l| l.iter()).fold(TerseMarks::default(), |mut marks, c| {
+        match *c {
+            b'.' => marks.passed += 1,
+            b'F' => marks.failed += 1,
+            _ => marks.ignored += 1,
+        }
+        marks
+    })
+}
+
+fn handle_parsed_suite<'a>(
+    line: SuiteLine<'a>,
+    tests: Vec<borrowed::Test<'a>>,
+    marks: Option<TerseMarks>,
+    failures: Option<FailureSection<'a>>,
     result: SuiteResult,
-) -> Suite {
-    let tests_with_failures = match failures {
-        Some(xs) => {
+) -> borrowed::Suite<'a> {
+    let name = line.name;
+    let name_lossy = line.lossy;
+    let crate_name = suite_crate_name(&name);
+
+    if let Some(marks) = marks {
+        let section = failures.unwrap_or_default();
+        let unmatched = unmatched_failures(&section.failures, section.names.iter().map(|n| &*n.0));
+        let index = index_failures(section.failures);
+        let tests: Vec<borrowed::Test> = section
+            .names
+            .into_iter()
+            .map(|(n, lossy)| {
+                let failure = index.get(&*n);
+
+                borrowed::Test {
+                    lossy: lossy || failure.is_some_and(|f| f.1),
+                    error: failure.map(|f| f.0.clone()),
+                    name: n,
+                    status: TestStatus::Failed,
+                    ..Default::default()
+                }
+            })
+            .collect();
+
+        return borrowed::Suite {
+            lossy: name_lossy || tests.iter().any(|t| t.lossy),
+            name,
+            tests,
+            state: result.state,
+            total: marks.passed + marks.failed + marks.ignored,
+            passed: marks.passed,
+            failed: marks.failed,
+            ignored: marks.ignored,
+            measured: result.measured,
+            anonymous_tests: true,
+            package: None,
+            crate_name: Some(crate_name),
+            kind: line.kind,
+            source: line.source,
+            binary: line.binary,
+            duration: result.duration,
+            unmatched_failures: unmatched,
+        };
+    }
+
+    let mut unmatched = vec![];
+    let tests_with_failures: Vec<borrowed::Test> = match failures {
+        Some(section) => {
+            unmatched = unmatched_failures(&section.failures, tests.iter().map(|t| &*t.name));
+            let index = index_failures(section.failures);
+
             tests
-                .iter()
-                .map(|t| {
-                    Test {
-                        error: find_message_by_name(&t.name, &xs),
-                        name: t.name.to_string(),
-                        status: t.status.to_string(),
+                .into_iter()
+                .map(|mut t| {
+                    if let Some(&(ref error, lossy)) = index.get(&*t.name) {
+                        t.error = Some(error.clone());
+                        t.lossy |= lossy;
                     }
+                    t
                 })
                 .collect()
         }
         None => tests,
     };
 
-    Suite {
-        name: name,
+    borrowed::Suite {
+        lossy: name_lossy || tests_with_failures.iter().any(|t| t.lossy),
+        name,
         tests: tests_with_failures,
-        state: result.state.to_string(),
+        state: result.state,
         total: result.total,
         passed: result.passed,
         failed: result.failed,
         ignored: result.ignored,
         measured: result.measured,
+        anonymous_tests: false,
+        package: None,
+        crate_name: Some(crate_name),
+        kind: line.kind,
+        source: line.source,
+        binary: line.binary,
+        duration: result.duration,
+        unmatched_failures: unmatched,
     }
 }
 
+/// The time `--report-time` prints after a status, as in `ok <0.012s>`.
+fn report_time(rest: &str) -> Option<f64> {
+    rest.strip_prefix(" <")?.strip_suffix("s>")?.parse().ok()
+}
+
+fn is_status_end(status: TestStatus, rest: &[u8]) -> bool {
+    match status {
+        TestStatus::Ignored => rest.is_empty() || rest.starts_with(b", "),
+        TestStatus::Bench => rest.starts_with(b":"),
+        _ => rest.is_empty() || str::from_utf8(rest).ok().and_then(report_time).is_some(),
+    }
+}
+
+/// Splits `name ... status` on the last ` ... ` that is followed by a status,
+/// as names may contain ` ... ` themselves. An ignored test can carry a
+/// reason, as in `ignored, needs network`, a benchmark its timing, as in
+/// `bench:       1,234 ns/iter (+/- 56)`, and any other test its duration,
+/// as in `ok <0.012s>`.
+pub fn split_status(line: &str) -> Option<(&str, TestStatus)> {
+    line.rmatch_indices(" ... ").find_map(|(i, marker)| {
+        match test_status(&line.as_bytes()[i + marker.len()..]) {
+            IResult::Done(rest, status) if is_status_end(status, rest) => {
+                Some((&line[..i], status))
+            }
+            _ => None,
+        }
+    })
+}
+
+fn test_line((line, lossy): (Cow<str>, bool)) -> Option<borrowed::Test> {
+    let trimmed = line.trim_end();
+    let (name, status) = split_status(trimmed)?;
+    let len = name.len();
+    let duration = match trimmed.rfind(" <") {
+        Some(i) if i > len && status != TestStatus::Ignored => report_time(&trimmed[i..]),
+        _ => None,
+    };
+
+    Some(borrowed::Test {
+        lossy,
+        name: map_text(line, |l| &l[..len]),
+        status,
+        error: None,
+        stdout: None,
+        duration,
+    })
+}
+
 named!(
-    test_result<Test>,
+    test_result<&[u8], borrowed::Test<'_> >,
     do_parse!(
         tag!("test") >>
         space >>
-        name: map_res!(
-            take_until_s!(" ..."),
-            str::from_utf8
+        test: map_opt!(
+            map!(not_line_ending, decode),
+            test_line
         ) >>
-        tag!(" ...") >>
-        status: ws!(ok_or_failed) >>
-        (Test {
-            name: name.to_string(),
-            status: status.to_string(),
-            error: None
-        })
+        opt!(complete!(multispace)) >>
+        (test)
     )
 );
 
 named!(
-    test_results<Vec<Test> >,
+    test_results<&[u8], Vec<borrowed::Test<'_> > >,
     many0!(
         test_result
     )
 );
 
+fn is_terse_mark(c: u8) -> bool {
+    c == b'.' || c == b'F' || c == b'i'
+}
+
 named!(
-    suite_line<&str>,
+    terse_line<&[u8], &[u8]>,
     do_parse!(
-        ws!(
-            alt!(tag!("Running") | tag!("Doc-tests"))
+        marks: take_while1!(is_terse_mark) >>
+        opt!(
+            complete!(
+                do_parse!(space >> digit >> char!('/') >> digit >> ())
+            )
         ) >>
-        name: rest_of_line >>
-        (name)
+        line_ending >>
+        (marks)
+    )
+);
+
+named!(
+    terse_results<TerseMarks>,
+    map!(many1!(complete!(terse_line)), count_marks)
+);
+
+named!(
+    suite_line<&[u8], SuiteLine<'_> >,
+    alt!(
+        do_parse!(
+            ws!(tag!("Running")) >>
+            name: decoded_line >>
+            (running_line(name.0, name.1))
+        ) |
+        do_parse!(
+            ws!(tag!("Doc-tests")) >>
+            name: decoded_line >>
+            (doc_tests_line(name.0, name.1))
+        )
     )
 );
 
@@ -112,29 +344,61 @@ named!(
     )
 );
 
-named!(
-    suite_parser<Suite>,
-    do_parse!(
-        name: suite_line >>
-        suite_count >>
-        tests: test_results >>
-        failures: fail_opt >>
-        result: suite_result >>
-        (handle_parsed_suite(name.to_string(), tests, failures, result))
-    )
-);
+/// The value parsed and the input left, or the input left at the part that
+/// could not be parsed and what was expected there.
+pub type Stage<'a, T> = Result<(&'a [u8], T), (&'a [u8], Expected)>;
+
+fn stage<'a, T>(result: IResult<&'a [u8], T>, input: &'a [u8], expected: Expected) -> Stage<'a, T> {
+    match result {
+        IResult::Done(rest, value) => Ok((rest, value)),
+        _ => Err((input, expected)),
+    }
+}
+
+/// Parses one suite like `suite_parser`, but tells where it stopped.
+pub fn parse_suite(input: &[u8]) -> Stage<'_, borrowed::Suite<'_>> {
+    let (rest, line) = stage(suite_line(input), input, Expected::SuiteHeader)?;
+    let (rest, _) = stage(suite_count(rest), rest, Expected::TestCount)?;
+    let (rest, marks) = match terse_results(rest) {
+        IResult::Done(rest, marks) => (rest, Some(marks)),
+        _ => (rest, None),
+    };
+    let (rest, tests) = match test_results(rest) {
+        IResult::Done(rest, tests) => (rest, tests),
+        _ => (rest, vec![]),
+    };
+    let (rest, failures) = stage(fail_opt(rest), rest, Expected::FailureSection)?;
+    let (rest, result) = stage(result_line(rest), rest, Expected::ResultLine)?;
+
+    Ok((rest, handle_parsed_suite(line, tests, marks, failures, result)))
+}
+
+fn suite_parser(input: &[u8]) -> IResult<&[u8], borrowed::Suite<'_>> {
+    match parse_suite(input) {
+        Ok((rest, suite)) => IResult::Done(rest, suite),
+        Err(_) => IResult::Error(ErrorKind::Many1),
+    }
+}
 
 named!(
-    pub suites_parser<Vec<Suite > >,
+    pub suites_parser<&[u8], Vec<borrowed::Suite<'_> > >,
     many1!(suite_parser)
 );
 
+pub fn into_owned(suites: Vec<borrowed::Suite>) -> Vec<Suite> {
+    suites.into_iter().map(borrowed::Suite::into_owned).collect()
+}
+
 #[cfg(test)]
 mod tests {
     use nom::IResult;
     use std::fmt::Debug;
 
-    use super::{suite_line, suite_count, Test, test_result, test_results};
+    use super::{
+        suite_line, suite_count, terse_results, SuiteKind, SuiteLine, TerseMarks, TestStatus,
+        test_result, test_results,
+    };
+    use borrowed::Test;
 
     fn assert_done<R: PartialEq + Debug>(l: IResult<&[u8], R>, r: R) {
         assert_eq!(
@@ -150,7 +414,35 @@ mod tests {
 "[..],
         );
 
-        assert_done(result, "target/debug/deps/docker_command-be014e20fbd07382");
+        assert_done(
+            result,
+            SuiteLine {
+                name: "target/debug/deps/docker_command-be014e20fbd07382".into(),
+                kind: SuiteKind::Unknown,
+                source: None,
+                binary: Some("target/debug/deps/docker_command-be014e20fbd07382".into()),
+                lossy: false,
+            },
+        );
+    }
+
+    #[test]
+    fn it_should_parse_a_doc_tests_line() {
+        let result = suite_line(
+            &b"   Doc-tests docker_command
+"[..],
+        );
+
+        assert_done(
+            result,
+            SuiteLine {
+                name: "docker_command".into(),
+                kind: SuiteKind::Doc,
+                source: None,
+                binary: None,
+                lossy: false,
+            },
+        );
     }
 
     #[test]
@@ -170,9 +462,53 @@ mod tests {
         assert_done(
             result,
             Test {
-                name: "it_runs_a_command".to_string(),
-                status: "pass".to_string(),
-                error: None,
+                name: "it_runs_a_command".into(),
+                status: TestStatus::Passed,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn it_should_parse_a_bench_result() {
+        let result = test_result(&b"test bench_parse ... bench:       1,234 ns/iter (+/- 56)"[..]);
+
+        assert_done(
+            result,
+            Test {
+                name: "bench_parse".into(),
+                status: TestStatus::Bench,
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn it_should_parse_a_reported_time() {
+        let result = test_result(&b"test tests::it_waits ... FAILED <1.250s>"[..]);
+
+        assert_done(
+            result,
+            Test {
+                name: "tests::it_waits".into(),
+                status: TestStatus::Failed,
+                duration: Some(1.25),
+                ..Default::default()
+            },
+        );
+    }
+
+    #[test]
+    fn it_should_replace_invalid_utf8_in_test_names() {
+        let result = test_result(&b"test caf\xe9_menu ... ok"[..]);
+
+        assert_done(
+            result,
+            Test {
+                name: "caf\u{fffd}_menu".into(),
+                status: TestStatus::Passed,
+                lossy: true,
+                ..Default::default()
             },
         );
     }
@@ -193,26 +529,44 @@ test tests::it_should_parse_suite_line ... FAILED
 
             vec![
                 Test {
-                    name: "tests::it_should_parse_first_line".to_string(),
-                    status: "pass".to_string(),
-                    error: None
+                    name: "tests::it_should_parse_first_line".into(),
+                    status: TestStatus::Passed,
+                    ..Default::default()
                 },
                 Test {
-                    name: "tests::it_should_parse_a_status_line".to_string(),
-                    status: "pass".to_string(),
-                    error: None
+                    name: "tests::it_should_parse_a_status_line".into(),
+                    status: TestStatus::Passed,
+                    ..Default::default()
                 },
                 Test {
-                    name: "tests::it_should_parse_test_output".to_string(),
-                    status: "pass".to_string(),
-                    error: None
+                    name: "tests::it_should_parse_test_output".into(),
+                    status: TestStatus::Passed,
+                    ..Default::default()
                 },
                 Test {
-                    name: "tests::it_should_parse_suite_line".to_string(),
-                    status: "fail".to_string(),
-                    error: None
+                    name: "tests::it_should_parse_suite_line".into(),
+                    status: TestStatus::Failed,
+                    ..Default::default()
                 }
             ],
         );
     }
+
+    #[test]
+    fn it_should_count_terse_results() {
+        let result = terse_results(
+            &b"........................................................................................ 88/100
+.....F..i...
+"[..],
+        );
+
+        assert_done(
+            result,
+            TerseMarks {
+                passed: 98,
+                failed: 1,
+                ignored: 1,
+            },
+        );
+    }
 }
@@ -0,0 +1,149 @@
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use borrowed;
+use suite::{Suite, SuiteKind};
+
+/// The test binary a `Running` line names, with or without the source file
+/// newer cargo prints in front of it.
+pub fn binary_path(suite_name: &str) -> &str {
+    match suite_name.rfind('(') {
+        Some(i) if suite_name.ends_with(')') => &suite_name[i + 1..suite_name.len() - 1],
+        _ => suite_name,
+    }
+}
+
+fn is_hash(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Test binaries are named `<crate>-<hash>`, plus `.exe` on Windows, while
+/// `Doc-tests` lines name the crate directly, with dashes on older cargo.
+pub fn crate_name(suite_name: &str) -> Cow<'_, str> {
+    let binary = binary_path(suite_name);
+    let file = binary.rsplit(['/', '\\']).next().unwrap_or(binary);
+    let file = file.strip_suffix(".exe").unwrap_or(file);
+
+    let name = match file.rsplit_once('-') {
+        Some((stem, hash)) if is_hash(hash) => stem,
+        _ => file,
+    };
+
+    if name.contains('-') {
+        Cow::Owned(name.replace('-', "_"))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+/// `crate_name`, borrowing from the log when the suite name does.
+pub fn suite_crate_name<'a>(suite_name: &Cow<'a, str>) -> Cow<'a, str> {
+    match *suite_name {
+        Cow::Borrowed(name) => crate_name(name),
+        Cow::Owned(ref name) => Cow::Owned(crate_name(name).into_owned()),
+    }
+}
+
+fn is_named_after_file(suite: &borrowed::Suite) -> bool {
+    match suite.kind {
+        SuiteKind::Integration | SuiteKind::Bench | SuiteKind::Example => true,
+        SuiteKind::Unit | SuiteKind::Doc => false,
+        SuiteKind::Unknown => suite.name.contains(['/', '\\']),
+    }
+}
+
+/// Attaches the package each suite belongs to, given the local packages
+/// `Compiling` lines reported. Library, binary and doc test suites are named
+/// after a crate of their package. Integration tests, benches and examples are
+/// named after their file instead, so they take the package of the suite
+/// before them, as cargo runs the test binaries of one package after another.
+pub fn assign_packages<'a>(
+    mut suites: Vec<borrowed::Suite<'a>>,
+    packages: &[String],
+) -> Vec<borrowed::Suite<'a>> {
+    let by_crate: HashMap<String, &String> =
+        packages.iter().map(|p| (p.replace('-', "_"), p)).collect();
+    let mut current: Option<&String> = None;
+
+    for suite in &mut suites {
+        let package = suite.crate_name.as_ref().and_then(|name| by_crate.get(&**name).cloned());
+
+        if package.is_some() || !is_named_after_file(suite) {
+            current = package;
+        }
+        suite.package = current.map(|p| Cow::Owned(p.clone()));
+    }
+
+    suites
+}
+
+/// Groups suites by `Suite.package`, in the order packages first appear.
+pub fn group_by_package(suites: &[Suite]) -> Vec<(Option<&str>, Vec<&Suite>)> {
+    let mut groups: Vec<(Option<&str>, Vec<&Suite>)> = vec![];
+
+    for suite in suites {
+        let package = suite.package.as_deref();
+
+        match groups.iter_mut().find(|g| g.0 == package) {
+            Some(group) => group.1.push(suite),
+            None => groups.push((package, vec![suite])),
+        }
+    }
+
+    groups
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{assign_packages, crate_name};
+    use borrowed::Suite;
+    use suite::SuiteKind;
+
+    #[test]
+    fn it_should_take_the_crate_name_from_the_binary() {
+        assert_eq!(crate_name("target/debug/deps/docker_command-be014e20fbd07382"), "docker_command");
+        assert_eq!(
+            crate_name("unittests src/lib.rs (target/debug/deps/api_server-622eefdc86aa5319)"),
+            "api_server"
+        );
+        assert_eq!(
+            crate_name("target\\debug\\deps\\cargo_results-9d3e2f7b5f1e3c6a.exe"),
+            "cargo_results"
+        );
+        assert_eq!(crate_name("api_server"), "api_server");
+    }
+
+    #[test]
+    fn it_should_give_integration_tests_the_preceding_package() {
+        let suite = |name: &'static str, kind: SuiteKind| Suite {
+            name: name.into(),
+            crate_name: Some(crate_name(name)),
+            kind,
+            ..Default::default()
+        };
+        let suites = vec![
+            suite("unittests src/lib.rs (target/debug/deps/api_server-622eefdc86aa5319)", SuiteKind::Unit),
+            suite("tests/routes.rs (target/debug/deps/routes-0b1c2d3e4f5a6b7c)", SuiteKind::Integration),
+            suite("unittests src/lib.rs (target/debug/deps/cached-1a2b3c4d5e6f7a8b)", SuiteKind::Unit),
+            suite("tests/store.rs (target/debug/deps/store-8b7a6f5e4d3c2b1a)", SuiteKind::Integration),
+            suite("api_server", SuiteKind::Doc),
+        ];
+        let packages = vec!["api-server".to_string(), "core".to_string()];
+
+        let packages: Vec<Option<String>> = assign_packages(suites, &packages)
+            .into_iter()
+            .map(|s| s.into_owned().package)
+            .collect();
+
+        assert_eq!(
+            packages,
+            vec![
+                Some("api-server".to_string()),
+                Some("api-server".to_string()),
+                None,
+                None,
+                Some("api-server".to_string()),
+            ]
+        );
+    }
+}
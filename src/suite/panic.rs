@@ -0,0 +1,149 @@
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use suite::{Test, COMPILE_FAILED};
+
+/// Where a test panicked, as in `src/lib.rs:12:9`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct Location<'a> {
+    pub file: &'a str,
+    pub line: u32,
+    /// Old Rust releases reported only the line.
+    pub column: Option<u32>,
+}
+
+/// Parses `file:line:column` or `file:line`.
+pub fn location(text: &str) -> Option<Location<'_>> {
+    let (head, last) = text.rsplit_once(':')?;
+    let last = last.parse().ok()?;
+
+    if let Some((file, line)) = head.rsplit_once(':') {
+        if let (false, Ok(line)) = (file.is_empty(), line.parse()) {
+            return Some(Location {
+                file,
+                line,
+                column: Some(last),
+            });
+        }
+    }
+    if head.is_empty() {
+        return None;
+    }
+    Some(Location {
+        file: head,
+        line: last,
+        column: None,
+    })
+}
+
+/// The `--> src/lib.rs:3:5` line of a rustc error.
+pub fn compile_error_location(error: &str) -> Option<Location<'_>> {
+    error
+        .lines()
+        .filter_map(|line| line.trim_start().strip_prefix("--> "))
+        .find_map(|site| location(site.trim_end()))
+}
+
+/// Splits a panic into its message and location. Rust 1.73 and later put
+/// the location first, as in `panicked at src/lib.rs:12:9:\nmessage`;
+/// older releases wrote `panicked at 'message', src/lib.rs:12:9`.
+fn panic_site(error: &str) -> Option<(&str, Location<'_>)> {
+    const PANICKED: &str = "panicked at ";
+
+    let start = error.find(PANICKED)? + PANICKED.len();
+    let rest = &error[start..];
+
+    if let Some(quoted) = rest.strip_prefix('\'') {
+        return quoted.match_indices("', ").find_map(|(i, _)| {
+            let after = &quoted[i + 3..];
+            let end = after.find('\n').unwrap_or(after.len());
+
+            location(after[..end].trim_end()).map(|l| (&quoted[..i], l))
+        });
+    }
+
+    let end = rest.find('\n').unwrap_or(rest.len());
+    let site = location(rest[..end].trim_end().trim_end_matches(':'))?;
+    let message = rest.get(end + 1..).unwrap_or("").trim_matches('\n');
+
+    Some((message, site))
+}
+
+impl Test {
+    /// Whether the test stands in for a compile error rather than being run.
+    pub fn is_compile_error(&self) -> bool {
+        self.name == COMPILE_FAILED
+    }
+
+    /// Where the test panicked, if its error says.
+    pub fn panic_location(&self) -> Option<Location<'_>> {
+        self.error.as_ref().and_then(|e| panic_site(e)).map(|(_, l)| l)
+    }
+
+    /// The panic message without the thread and location, falling back to
+    /// the first line of the error.
+    pub fn panic_message(&self) -> Option<&str> {
+        let error = self.error.as_ref()?;
+
+        match panic_site(error) {
+            Some((message, _)) if !message.is_empty() => Some(message),
+            _ => error.lines().next(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Location;
+    use suite::{Test, TestStatus};
+
+    fn failed(error: &str) -> Test {
+        Test {
+            name: "it_fails".to_string(),
+            status: TestStatus::Failed,
+            error: Some(error.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_should_find_where_a_test_panicked() {
+        let current = failed(
+            "thread 'it_fails' panicked at src/lib.rs:12:9:\nassertion `left == right` failed\n  left: 1\n right: 2",
+        );
+        let quoted = failed(
+            "thread 'it_fails' panicked at 'assertion failed: `(left == right)`', tests/integration_test.rs:16\nnote: run with `RUST_BACKTRACE=1`",
+        );
+
+        assert_eq!(
+            current.panic_location(),
+            Some(Location {
+                file: "src/lib.rs",
+                line: 12,
+                column: Some(9),
+            })
+        );
+        assert_eq!(
+            current.panic_message(),
+            Some("assertion `left == right` failed\n  left: 1\n right: 2")
+        );
+        assert_eq!(
+            quoted.panic_location(),
+            Some(Location {
+                file: "tests/integration_test.rs",
+                line: 16,
+                column: None,
+            })
+        );
+        assert_eq!(quoted.panic_message(), Some("assertion failed: `(left == right)`"));
+    }
+
+    #[test]
+    fn it_should_fall_back_to_the_first_line() {
+        let test = failed("error: test exceeded 60s\nkilled");
+
+        assert_eq!(test.panic_location(), None);
+        assert_eq!(test.panic_message(), Some("error: test exceeded 60s"));
+    }
+}
@@ -1,20 +1,33 @@
-use utility_parsers::{ok_or_failed, digits};
+use nom::{ErrorKind, IResult};
+use std::str;
+
+use suite::{SuiteState, TestStatus};
+use utility_parsers::{ok_or_failed, digits, skip_blank_lines, split_line};
 
 #[derive(Debug, PartialEq)]
-pub struct SuiteResult<'a> {
-    pub state: &'a str,
+pub struct SuiteResult {
+    pub state: SuiteState,
     pub passed: i64,
     pub failed: i64,
     pub ignored: i64,
     pub total: i64,
     pub measured: i64,
+    pub duration: Option<f64>,
+}
+
+fn suite_state(status: TestStatus) -> SuiteState {
+    if status.is_failure() {
+        SuiteState::Failed
+    } else {
+        SuiteState::Passed
+    }
 }
 
 named!(
     pub suite_result<SuiteResult>,
     do_parse!(
         ws!(tag!("test result: ")) >>
-        state: ok_or_failed >>
+        state: map!(ok_or_failed, suite_state) >>
         char!('.') >>
         passed: digits >>
         tag!("passed;") >>
@@ -25,27 +38,52 @@ named!(
         measured: digits >>
         tag!("measured;") >>
         digits >>
-        ws!(tag!("filtered out;")) >>
-        ws!(tag!("finished in")) >>
-        take_until!("s") >>
-        ws!(char!('s')) >>
+        ws!(tag!("filtered out")) >>
+        duration: opt!(
+            complete!(
+                do_parse!(
+                    char!(';') >>
+                    ws!(tag!("finished in")) >>
+                    seconds: map_res!(
+                        map_res!(take_until!("s"), str::from_utf8),
+                        str::FromStr::from_str
+                    ) >>
+                    ws!(char!('s')) >>
+                    (seconds)
+                )
+            )
+        ) >>
         (SuiteResult {
-          state:state,
-          passed:passed,
-          failed:failed,
-          ignored:ignored,
+          state,
+          passed,
+          failed,
+          ignored,
           total: passed + failed + ignored,
-          measured:measured
+          measured,
+          duration
         })
     )
 );
 
+/// `suite_result` confined to the next non-blank line, so that a malformed
+/// result line cannot send it searching through the rest of the log.
+pub fn result_line(input: &[u8]) -> IResult<&[u8], SuiteResult> {
+    match split_line(skip_blank_lines(input)) {
+        Some((line, rest)) => match suite_result(line) {
+            IResult::Done(_, result) => IResult::Done(skip_blank_lines(rest), result),
+            _ => IResult::Error(ErrorKind::Tag),
+        },
+        None => IResult::Incomplete(::nom::Needed::Unknown),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use nom::IResult;
     use std::fmt::Debug;
 
     use super::{SuiteResult, suite_result};
+    use suite::SuiteState;
 
     fn assert_done<R: PartialEq + Debug>(l: IResult<&[u8], R>, r: R) {
         assert_eq!(
@@ -63,12 +101,13 @@ mod tests {
         assert_done(
             result,
             SuiteResult {
-                state: "fail",
+                state: SuiteState::Failed,
                 passed: 3,
                 failed: 1,
                 ignored: 0,
                 total: 4,
                 measured: 0,
+                duration: Some(0.0),
             },
         );
     }
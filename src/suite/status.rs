@@ -0,0 +1,115 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Serialized as its `as_str()` name, as are `SuiteState` and `SuiteKind`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum TestStatus {
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "pass"))]
+    Passed,
+    #[cfg_attr(feature = "serde", serde(rename = "fail"))]
+    Failed,
+    #[cfg_attr(feature = "serde", serde(rename = "ignored"))]
+    Ignored,
+    /// A `#[bench]` that ran and reported its timing.
+    #[cfg_attr(feature = "serde", serde(rename = "bench"))]
+    Bench,
+    /// Killed by the runner for exceeding its time limit, as nextest reports.
+    #[cfg_attr(feature = "serde", serde(rename = "timeout"))]
+    Timeout,
+    /// The test process aborted or died from a signal.
+    #[cfg_attr(feature = "serde", serde(rename = "crashed"))]
+    Crashed,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum SuiteState {
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "pass"))]
+    Passed,
+    #[cfg_attr(feature = "serde", serde(rename = "fail"))]
+    Failed,
+}
+
+impl TestStatus {
+    /// The names the crate used before statuses were typed: `"pass"`,
+    /// `"fail"`, `"ignored"` and so on.
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            TestStatus::Passed => "pass",
+            TestStatus::Failed => "fail",
+            TestStatus::Ignored => "ignored",
+            TestStatus::Bench => "bench",
+            TestStatus::Timeout => "timeout",
+            TestStatus::Crashed => "crashed",
+        }
+    }
+
+    pub fn is_failure(&self) -> bool {
+        match *self {
+            TestStatus::Failed | TestStatus::Timeout | TestStatus::Crashed => true,
+            TestStatus::Passed | TestStatus::Ignored | TestStatus::Bench => false,
+        }
+    }
+}
+
+impl SuiteState {
+    pub fn as_str(&self) -> &'static str {
+        match *self {
+            SuiteState::Passed => "pass",
+            SuiteState::Failed => "fail",
+        }
+    }
+}
+
+impl fmt::Display for TestStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl fmt::Display for SuiteState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl PartialEq<str> for TestStatus {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for TestStatus {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+impl PartialEq<str> for SuiteState {
+    fn eq(&self, other: &str) -> bool {
+        self.as_str() == other
+    }
+}
+
+impl<'a> PartialEq<&'a str> for SuiteState {
+    fn eq(&self, other: &&'a str) -> bool {
+        self.as_str() == *other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SuiteState, TestStatus};
+
+    #[test]
+    fn it_should_compare_with_the_old_strings() {
+        assert_eq!(TestStatus::Failed, "fail");
+        assert_eq!(TestStatus::Ignored.to_string(), "ignored");
+        assert!(SuiteState::Passed == "pass");
+        assert!(SuiteState::Failed != "pass");
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(feature = "serde")]
+use serde::Serialize;
+
+use suite::{Suite, Test, TestStatus};
+
+/// A module of a suite's tests, as in `router::handlers` for
+/// `router::handlers::tests::test_success`. Counts and duration cover every
+/// test below the module, not only those directly in it.
+#[derive(Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+pub struct ModuleNode<'a> {
+    /// The last segment of `path`, such as `handlers`; empty for the root.
+    pub name: &'a str,
+    /// Empty for the root.
+    pub path: &'a str,
+    pub modules: Vec<ModuleNode<'a>>,
+    /// Tests directly in this module, named by what follows `path::`.
+    pub tests: Vec<&'a Test>,
+    pub passed: i64,
+    pub failed: i64,
+    pub ignored: i64,
+    /// Seconds taken by the tests below that report a duration, if any do.
+    pub duration: Option<f64>,
+}
+
+/// The module part of a test name. Splitting stops at the first space, so
+/// doc tests such as `src/lib.rs - router::Route (line 12)` stay whole.
+fn module_path(name: &str) -> &str {
+    let word = name.split(' ').next().unwrap_or(name);
+
+    word.rfind("::").map_or("", |i| &name[..i])
+}
+
+impl<'a> ModuleNode<'a> {
+    fn count(&mut self, test: &Test) {
+        match test.status {
+            TestStatus::Passed | TestStatus::Bench => self.passed += 1,
+            TestStatus::Ignored => self.ignored += 1,
+            TestStatus::Failed | TestStatus::Timeout | TestStatus::Crashed => self.failed += 1,
+        }
+        if let Some(duration) = test.duration {
+            self.duration = Some(self.duration.unwrap_or(0.0) + duration);
+        }
+    }
+
+    fn module(&mut self, name: &'a str, path: &'a str) -> &mut ModuleNode<'a> {
+        match self.modules.iter().position(|m| m.name == name) {
+            Some(i) => &mut self.modules[i],
+            None => {
+                self.modules.push(ModuleNode {
+                    name,
+                    path,
+                    ..Default::default()
+                });
+                self.modules.last_mut().unwrap()
+            }
+        }
+    }
+
+    pub fn total(&self) -> i64 {
+        self.passed + self.failed + self.ignored
+    }
+
+    /// The module at `path`, such as `router::handlers`, below this one.
+    pub fn find(&self, path: &str) -> Option<&ModuleNode<'a>> {
+        path.split("::")
+            .try_fold(self, |node, name| node.modules.iter().find(|m| m.name == name))
+    }
+}
+
+impl Suite {
+    /// Groups the tests by the modules in their names, keeping the order in
+    /// which modules and tests first appear. The tree of an
+    /// `anonymous_tests` suite holds only its failed tests.
+    pub fn module_tree(&self) -> ModuleNode<'_> {
+        let mut root = ModuleNode::default();
+
+        for test in &self.tests {
+            let path = module_path(&test.name);
+            let mut node = &mut root;
+
+            node.count(test);
+            if !path.is_empty() {
+                let mut start = 0;
+
+                for name in path.split("::") {
+                    let end = start + name.len();
+
+                    node = node.module(name, &path[..end]);
+                    node.count(test);
+                    start = end + 2;
+                }
+            }
+            node.tests.push(test);
+        }
+
+        root
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use suite::{Suite, Test, TestStatus};
+
+    fn test(name: &str, status: TestStatus, duration: Option<f64>) -> Test {
+        Test {
+            name: name.to_string(),
+            status,
+            duration,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn it_should_nest_tests_by_module() {
+        let suite = Suite {
+            tests: vec![
+                test("router::handlers::tests::test_success", TestStatus::Passed, Some(0.5)),
+                test("router::handlers::tests::test_failure", TestStatus::Failed, Some(0.25)),
+                test("router::tests::test_routes", TestStatus::Ignored, None),
+                test("it_starts", TestStatus::Passed, None),
+                test("src/lib.rs - router::Route (line 12)", TestStatus::Passed, None),
+            ],
+            ..Default::default()
+        };
+
+        let root = suite.module_tree();
+
+        assert_eq!((root.passed, root.failed, root.ignored, root.total()), (3, 1, 1, 5));
+        assert_eq!(root.duration, Some(0.75));
+        assert_eq!(root.tests.len(), 2);
+        assert_eq!(root.modules.len(), 1);
+
+        let router = &root.modules[0];
+        assert_eq!((router.name, router.path), ("router", "router"));
+        assert_eq!((router.passed, router.failed, router.ignored), (1, 1, 1));
+        assert_eq!(router.modules.iter().map(|m| m.name).collect::<Vec<_>>(), vec!["handlers", "tests"]);
+
+        let tests = root.find("router::handlers::tests").unwrap();
+        assert_eq!(tests.path, "router::handlers::tests");
+        assert_eq!(tests.tests.len(), 2);
+        assert_eq!(tests.duration, Some(0.75));
+        assert_eq!(root.find("router::missing"), None);
+    }
+}
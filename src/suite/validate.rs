@@ -0,0 +1,167 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+use suite::{Suite, TestStatus};
+
+/// A way in which a suite's parsed tests disagree with the rest of its
+/// output, which usually means lines were lost or mangled in the log.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Discrepancy {
+    /// A `---- name stdout ----` section names no test of the suite.
+    UnmatchedFailure(String),
+    /// A failed test has no captured output.
+    MissingFailureOutput(String),
+    /// The result line reports `reported` tests with `status`, but `found`
+    /// were parsed. `TestStatus::Bench` stands for `measured`.
+    CountMismatch {
+        status: TestStatus,
+        reported: i64,
+        found: i64,
+    },
+}
+
+fn count_label(status: TestStatus) -> &'static str {
+    match status {
+        TestStatus::Passed => "passed",
+        TestStatus::Ignored => "ignored",
+        TestStatus::Bench => "measured",
+        TestStatus::Failed | TestStatus::Timeout | TestStatus::Crashed => "failed",
+    }
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Discrepancy::UnmatchedFailure(ref name) => {
+                write!(f, "output for `{}` matches no test", name)
+            }
+            Discrepancy::MissingFailureOutput(ref name) => {
+                write!(f, "`{}` failed without output", name)
+            }
+            Discrepancy::CountMismatch { status, reported, found } => write!(
+                f,
+                "result line reports {} {}, but {} were parsed",
+                reported,
+                count_label(status),
+                found
+            ),
+        }
+    }
+}
+
+impl Suite {
+    fn count(&self, f: fn(&TestStatus) -> bool) -> i64 {
+        self.tests.iter().filter(|t| f(&t.status)).count() as i64
+    }
+
+    /// Checks the parsed tests against the failure output and the result
+    /// line. Of an `anonymous_tests` suite only the failed count is compared.
+    pub fn validate(&self) -> Vec<Discrepancy> {
+        let mut discrepancies: Vec<Discrepancy> = self
+            .unmatched_failures
+            .iter()
+            .map(|name| Discrepancy::UnmatchedFailure(name.clone()))
+            .collect();
+
+        discrepancies.extend(
+            self.tests
+                .iter()
+                .filter(|t| t.status.is_failure() && t.error.is_none())
+                .map(|t| Discrepancy::MissingFailureOutput(t.name.clone())),
+        );
+
+        let mut counts = vec![(TestStatus::Failed, self.failed, self.count(TestStatus::is_failure))];
+        if !self.anonymous_tests {
+            counts.push((TestStatus::Passed, self.passed, self.count(|s| *s == TestStatus::Passed)));
+            counts.push((TestStatus::Ignored, self.ignored, self.count(|s| *s == TestStatus::Ignored)));
+            counts.push((TestStatus::Bench, self.measured, self.count(|s| *s == TestStatus::Bench)));
+        }
+        discrepancies.extend(
+            counts
+                .into_iter()
+                .filter(|&(_, reported, found)| reported != found)
+                .map(|(status, reported, found)| Discrepancy::CountMismatch {
+                    status,
+                    reported,
+                    found,
+                }),
+        );
+
+        discrepancies
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Discrepancy;
+    use parse;
+    use suite::TestStatus;
+
+    #[test]
+    fn it_should_accept_a_consistent_suite() {
+        let output = b"     Running target/debug/deps/app-622eefdc86aa5319
+
+running 2 tests
+test it_fails ... FAILED
+test it_works ... ok
+
+failures:
+
+---- it_fails stdout ----
+thread 'it_fails' panicked at src/lib.rs:4:5
+
+failures:
+    it_fails
+
+test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+
+        assert_eq!(parse(output).unwrap().suites[0].validate(), vec![]);
+    }
+
+    #[test]
+    fn it_should_report_a_swallowed_result_line() {
+        let output = b"     Running target/debug/deps/app-622eefdc86aa5319
+
+running 4 tests
+test it_works ... ok
+test it_prints ... test it_fails ... FAILED
+test it_hangs ... FAILED
+
+failures:
+
+---- it_prints stdout ----
+thread 'it_prints' panicked at src/lib.rs:9:5
+
+failures:
+    it_prints
+    it_fails
+    it_hangs
+
+test result: FAILED. 1 passed; 3 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+";
+
+        let suite = &parse(output).unwrap().suites[0];
+        let discrepancies = suite.validate();
+
+        assert_eq!(
+            discrepancies,
+            vec![
+                Discrepancy::UnmatchedFailure("it_prints".to_string()),
+                Discrepancy::MissingFailureOutput("it_prints ... test it_fails".to_string()),
+                Discrepancy::MissingFailureOutput("it_hangs".to_string()),
+                Discrepancy::CountMismatch {
+                    status: TestStatus::Failed,
+                    reported: 3,
+                    found: 2,
+                },
+            ]
+        );
+        assert_eq!(
+            discrepancies[3].to_string(),
+            "result line reports 3 failed, but 2 were parsed"
+        );
+    }
+}
@@ -0,0 +1,186 @@
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use ansi::{plain, spell_out};
+use suite::{Suite, SuiteState, Test, TestStatus};
+
+const INDENT: &str = "    ";
+
+/// `#` starts a directive and `\` escapes it, so both are escaped in test
+/// descriptions.
+fn description(name: &str) -> Cow<'_, str> {
+    if !name.contains(['#', '\\']) {
+        return Cow::Borrowed(name);
+    }
+    Cow::Owned(name.replace('\\', "\\\\").replace('#', "\\#"))
+}
+
+/// `text` without ANSI escapes, with the other control characters, which
+/// YAML cannot hold, spelled out.
+fn yaml_text(text: &str) -> String {
+    spell_out(&plain(text), |c| c == '\t' || c == '\n' || !c.is_control()).into_owned()
+}
+
+/// A double-quoted YAML scalar. JSON strings are valid YAML, control
+/// characters escaped and all.
+fn quoted(text: &str) -> String {
+    serde_json::to_string(text).unwrap_or_default()
+}
+
+/// A literal block scalar. Its indentation is always given, as YAML would
+/// otherwise take it from the first non-blank line, indented or not.
+fn write_block<W: Write>(output: &mut W, indent: &str, key: &str, text: &str) -> io::Result<()> {
+    writeln!(output, "{}{}: |2-", indent, key)?;
+    for line in yaml_text(text).lines() {
+        if line.is_empty() {
+            writeln!(output)?;
+        } else {
+            writeln!(output, "{}  {}", indent, line)?;
+        }
+    }
+    Ok(())
+}
+
+/// The YAML diagnostic block of a failed test: its panic message and
+/// location, and everything it wrote.
+fn write_diagnostics<W: Write>(output: &mut W, indent: &str, test: &Test) -> io::Result<()> {
+    let yaml = format!("{}  ", indent);
+
+    writeln!(output, "{}---", yaml)?;
+    if let Some(message) = test.panic_message() {
+        writeln!(output, "{}message: {}", yaml, quoted(&plain(message)))?;
+    }
+    writeln!(output, "{}severity: {}", yaml, test.status)?;
+    if let Some(location) = test.panic_location() {
+        writeln!(output, "{}at:", yaml)?;
+        writeln!(output, "{}  file: {}", yaml, quoted(location.file))?;
+        writeln!(output, "{}  line: {}", yaml, location.line)?;
+        if let Some(column) = location.column {
+            writeln!(output, "{}  column: {}", yaml, column)?;
+        }
+    }
+    if let Some(duration) = test.duration {
+        writeln!(output, "{}duration_ms: {:.3}", yaml, duration * 1000.0)?;
+    }
+    let captured: Vec<&str> = test.error.iter().chain(test.stdout.iter()).map(|s| s.as_str()).collect();
+    if !captured.is_empty() {
+        write_block(output, &yaml, "output", &captured.join("\n"))?;
+    }
+    writeln!(output, "{}...", yaml)
+}
+
+fn write_test<W: Write>(output: &mut W, number: usize, test: &Test) -> io::Result<()> {
+    let name = description(&test.name);
+
+    match test.status {
+        TestStatus::Ignored => writeln!(output, "{}ok {} - {} # SKIP", INDENT, number, name),
+        status if status.is_failure() => {
+            writeln!(output, "{}not ok {} - {}", INDENT, number, name)?;
+            write_diagnostics(output, INDENT, test)
+        }
+        _ => writeln!(output, "{}ok {} - {}", INDENT, number, name),
+    }
+}
+
+/// A suite is a subtest, with its tests as test points indented below it.
+fn write_suite<W: Write>(output: &mut W, number: usize, suite: &Suite) -> io::Result<()> {
+    let name = description(&suite.name);
+
+    writeln!(output, "# Subtest: {}", name)?;
+    writeln!(output, "{}1..{}", INDENT, suite.tests.len())?;
+    for (i, test) in suite.tests.iter().enumerate() {
+        write_test(output, i + 1, test)?;
+    }
+
+    match suite.state {
+        SuiteState::Passed => writeln!(output, "ok {} - {}", number, name),
+        SuiteState::Failed => writeln!(output, "not ok {} - {}", number, name),
+    }
+}
+
+/// Writes suites as TAP version 14, one subtest per suite. Ignored tests are
+/// `# SKIP`ped, and failed ones carry a YAML block with their panic message,
+/// location and output. Of an `anonymous_tests` suite only the failed tests
+/// become test points.
+pub fn tap_writer<W: Write>(suites: &[Suite], mut output: W) -> io::Result<()> {
+    writeln!(output, "TAP version 14")?;
+    writeln!(output, "1..{}", suites.len())?;
+    for (i, suite) in suites.iter().enumerate() {
+        write_suite(&mut output, i + 1, suite)?;
+    }
+    output.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str;
+
+    use super::{description, tap_writer, write_block};
+    use fixtures::suites;
+
+    #[test]
+    fn it_should_write_subtests_per_suite() {
+        let mut output = vec![];
+
+        tap_writer(&suites(), &mut output).unwrap();
+
+        assert_eq!(
+            str::from_utf8(&output).unwrap(),
+            "TAP version 14
+1..3
+# Subtest: unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+    1..4
+    ok 1 - tests::it_works
+    not ok 2 - tests::it_fails
+      ---
+      message: \"100% `a < b`\\n```\"
+      severity: fail
+      at:
+        file: \"src/lib.rs\"
+        line: 12
+        column: 9
+      output: |2-
+        thread 'tests::it_fails' panicked at src/lib.rs:12:9:
+        100% `a < b`
+        ```
+      ...
+    not ok 3 - tests::it_hangs
+      ---
+      severity: timeout
+      ...
+    ok 4 - tests::it_is_slow # SKIP
+not ok 1 - unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+# Subtest: app
+    1..1
+    ok 1 - src/lib.rs - add (line 3)
+ok 2 - app
+# Subtest: unknown
+    1..1
+    not ok 1 - compile failed
+      ---
+      message: \"mismatched types\"
+      severity: fail
+      output: |2-
+        mismatched types
+         --> src/main.rs:3:5
+          |
+      ...
+not ok 3 - unknown
+"
+        );
+    }
+
+    #[test]
+    fn it_should_always_indent_blocks_explicitly() {
+        let mut output = vec![];
+
+        write_block(&mut output, "  ", "output", "\n  indented\nflush").unwrap();
+
+        assert_eq!(str::from_utf8(&output).unwrap(), "  output: |2-\n\n      indented\n    flush\n");
+    }
+
+    #[test]
+    fn it_should_escape_directives_in_descriptions() {
+        assert_eq!(description("tests::issue_#12"), "tests::issue_\\#12");
+    }
+}
@@ -0,0 +1,68 @@
+/// Percent-encodes all but unreserved characters and `/`.
+fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for &b in path.as_bytes() {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => encoded.push(b as char),
+            _ => encoded.push_str(&format!("%{:02X}", b)),
+        }
+    }
+    encoded
+}
+
+fn drive(path: &str) -> Option<&str> {
+    let bytes = path.as_bytes();
+
+    if bytes.len() > 2 && bytes[0].is_ascii_alphabetic() && bytes[1] == b':' && (bytes[2] == b'/' || bytes[2] == b'\\') {
+        Some(&path[..2])
+    } else {
+        None
+    }
+}
+
+/// Whether `path` is absolute on Unix or Windows, as the paths rustc gives
+/// for dependencies and the standard library are.
+pub fn is_absolute(path: &str) -> bool {
+    path.starts_with(['/', '\\']) || drive(path).is_some()
+}
+
+/// A relative path as a URI reference, with `/` as the separator.
+pub fn relative_uri(path: &str) -> String {
+    percent_encode(&path.replace('\\', "/"))
+}
+
+/// An absolute path as a `file://` URI.
+pub fn file_uri(path: &str) -> String {
+    let path = path.replace('\\', "/");
+
+    match drive(&path) {
+        Some(drive) => format!("file:///{}{}", drive, percent_encode(&path[2..])),
+        None => format!("file://{}", percent_encode(&path)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{file_uri, is_absolute, relative_uri};
+
+    #[test]
+    fn it_should_encode_relative_paths() {
+        assert!(!is_absolute("tests\\my tests (v2)#1.rs"));
+        assert_eq!(relative_uri("tests\\my tests (v2)#1.rs"), "tests/my%20tests%20%28v2%29%231.rs");
+        assert_eq!(relative_uri("src/100%/ünï.rs"), "src/100%25/%C3%BCn%C3%AF.rs");
+    }
+
+    #[test]
+    fn it_should_turn_absolute_paths_into_file_uris() {
+        let rustc = "/rustc/59807616e1fa2540724bfbac14d7976d7e4a3860/library/core/src/option.rs";
+
+        assert!(is_absolute(rustc));
+        assert_eq!(file_uri(rustc), format!("file://{}", rustc));
+        assert_eq!(
+            file_uri("/home/u/.cargo/registry/src/serde-1.0.0/src/de.rs"),
+            "file:///home/u/.cargo/registry/src/serde-1.0.0/src/de.rs"
+        );
+        assert!(is_absolute("C:\\Users\\u\\src\\de.rs"));
+        assert_eq!(file_uri("C:\\Users\\u\\my src\\de.rs"), "file:///C:/Users/u/my%20src/de.rs");
+    }
+}
@@ -1,12 +1,72 @@
+use memchr::memchr;
 use nom::{digit, line_ending, not_line_ending};
+use std::borrow::Cow;
 use std::str;
 
+use suite::TestStatus;
+
+/// Decodes log text, replacing bytes that are not valid UTF-8 with U+FFFD,
+/// and tells whether any were.
+pub fn decode(input: &[u8]) -> (Cow<'_, str>, bool) {
+    match str::from_utf8(input) {
+        Ok(text) => (Cow::Borrowed(text), false),
+        Err(_) => (String::from_utf8_lossy(input), true),
+    }
+}
+
+pub fn map_text<'a, F>(text: Cow<'a, str>, f: F) -> Cow<'a, str>
+where
+    F: for<'b> Fn(&'b str) -> &'b str,
+{
+    match text {
+        Cow::Borrowed(s) => Cow::Borrowed(f(s)),
+        Cow::Owned(s) => Cow::Owned(f(&s).to_string()),
+    }
+}
+
+/// Splits the first line off `input`, without its line ending. The last line
+/// of a log may lack one.
+pub fn split_line(input: &[u8]) -> Option<(&[u8], &[u8])> {
+    if input.is_empty() {
+        return None;
+    }
+
+    let (line, rest) = match memchr(b'\n', input) {
+        Some(i) => (&input[..i], &input[i + 1..]),
+        None => (input, &input[input.len()..]),
+    };
+
+    Some((line.strip_suffix(b"\r").unwrap_or(line), rest))
+}
+
+/// Skips lines that are empty or hold only whitespace, looking at each byte
+/// once.
+pub fn skip_blank_lines(mut input: &[u8]) -> &[u8] {
+    while let Some((line, rest)) = split_line(input) {
+        if !line.trim_ascii().is_empty() {
+            break;
+        }
+        input = rest;
+    }
+
+    input
+}
+
 named!(
-    pub rest_of_line<&str>,
+    pub decoded_line<&[u8], (Cow<'_, str>, bool)>,
     do_parse!(
-        content: map_res!(
+        content: map!(not_line_ending, decode) >>
+        line_ending >>
+        (content)
+    )
+);
+
+named!(
+    pub rest_of_line<&[u8], Cow<'_, str> >,
+    do_parse!(
+        content: map!(
             not_line_ending,
-            str::from_utf8
+            String::from_utf8_lossy
         ) >>
         line_ending >>
         (content)
@@ -14,22 +74,39 @@ named!(
 );
 
 named!(
-    pub ok<&str>,
+    pub ok<TestStatus>,
     map!(tag!("ok"),
-    |_| "pass")
+    |_| TestStatus::Passed)
 );
 
 named!(
-    pub failed<&str>,
+    pub failed<TestStatus>,
     map!(tag!("FAILED"),
-    |_| "fail")
+    |_| TestStatus::Failed)
 );
 
 named!(
-    pub ok_or_failed<&str>,
+    pub ok_or_failed<TestStatus>,
     alt!(ok | failed)
 );
 
+named!(
+    pub ignored<TestStatus>,
+    map!(tag!("ignored"),
+    |_| TestStatus::Ignored)
+);
+
+named!(
+    pub bench<TestStatus>,
+    map!(tag!("bench"),
+    |_| TestStatus::Bench)
+);
+
+named!(
+    pub test_status<TestStatus>,
+    alt!(ok_or_failed | ignored | bench)
+);
+
 named!(
     pub digits<i64>,
     map_res!(
@@ -46,7 +123,8 @@ mod tests {
     use nom::IResult;
     use std::fmt::Debug;
 
-    use super::{ok_or_failed, digits, rest_of_line};
+    use super::{ok_or_failed, digits, rest_of_line, skip_blank_lines, split_line, test_status};
+    use suite::TestStatus;
     
     fn assert_done<R: PartialEq + Debug>(l: IResult<&[u8], R>, r: R) {
         assert_eq!(
@@ -57,14 +135,19 @@ mod tests {
 
     #[test]
     fn it_should_match_ok() {
-        assert_done(ok_or_failed(&b"ok"[..]), "pass");
+        assert_done(ok_or_failed(&b"ok"[..]), TestStatus::Passed);
     }
 
     #[test]
     fn it_should_match_failed() {
-        assert_done(ok_or_failed(&b"FAILED"[..]), "fail");
+        assert_done(ok_or_failed(&b"FAILED"[..]), TestStatus::Failed);
     }
     
+    #[test]
+    fn it_should_match_ignored() {
+        assert_done(test_status(&b"ignored"[..]), TestStatus::Ignored);
+    }
+
     #[test]
     fn it_should_capture_digits() {
         assert_done(digits(b"10"), 10);
@@ -73,6 +156,14 @@ mod tests {
     #[test]
     fn it_should_match_to_end_of_line() {
         assert_done(rest_of_line(&b"this is a test
-"[..]), "this is a test");
+"[..]), "this is a test".into());
+    }
+
+    #[test]
+    fn it_should_split_lines_with_any_ending() {
+        assert_eq!(split_line(b"a\r\nb"), Some((&b"a"[..], &b"b"[..])));
+        assert_eq!(split_line(b"b"), Some((&b"b"[..], &b""[..])));
+        assert_eq!(split_line(b""), None);
+        assert_eq!(skip_blank_lines(b"\n  \r\n\t\n  x\n"), &b"  x\n"[..]);
     }
 }
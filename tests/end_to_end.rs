@@ -1,9 +1,14 @@
 extern crate cargo_results;
 extern crate nom;
+extern crate serde_json;
 
 use nom::IResult;
 use std::fmt::Debug;
-use cargo_results::{Test, cargo_test_result_parser, Suite};
+use cargo_results::{
+    annotation_writer, annotations, AnnotationFormat, BuildEvent, CiPrefix, Colors, SuiteKind, SuiteState, Test, TestStatus, Timing, cargo_json_parser, cargo_test_result_parser,
+    borrowed, group_by_package, junit_xml_parser, junit_xml_writer, markdown_writer, parse, sarif_writer, strip_ansi, strip_ci_prefixes, tap_writer, html_writer,
+    Suite,
+};
 
 fn assert_done<R: PartialEq + Debug>(l: IResult<&[u8], R>, r: R) {
     assert_eq!(
@@ -12,6 +17,32 @@ fn assert_done<R: PartialEq + Debug>(l: IResult<&[u8], R>, r: R) {
     )
 }
 
+fn assert_same_with_crlf(output: &[u8]) {
+    let mut crlf = vec![];
+    for c in output {
+        if *c == b'\n' {
+            crlf.push(b'\r');
+        }
+        crlf.push(*c);
+    }
+
+    match (cargo_test_result_parser(output), cargo_test_result_parser(&crlf)) {
+        (IResult::Done(_, lf_suites), IResult::Done(_, crlf_suites)) => {
+            assert_eq!(lf_suites, crlf_suites);
+            assert_eq!(parse(output).unwrap().suites, lf_suites);
+            assert_eq!(parse(&crlf).unwrap().suites, crlf_suites);
+
+            let borrowed: Vec<Suite> = borrowed::parse(output)
+                .unwrap()
+                .into_iter()
+                .map(borrowed::Suite::into_owned)
+                .collect();
+            assert_eq!(borrowed, lf_suites);
+        }
+        other => panic!("unexpected results {:?}", other),
+    }
+}
+
 #[test]
 fn it_should_parse_successful_test_output() {
     let output = &b"    Finished debug [unoptimized + debuginfo] target(s) in 0.0 secs
@@ -32,24 +63,32 @@ test tests::it_should_parse_first_line ... ok
         result,
         vec![Suite {
             name: "target/debug/cargo_test_junit-83252957c74e106d".to_string(),
-            state: "pass".to_string(),
+            package: None,
+            crate_name: Some("cargo_test_junit".to_string()),
+            kind: SuiteKind::Unknown,
+            source: None,
+            binary: Some("target/debug/cargo_test_junit-83252957c74e106d".to_string()),
+            state: SuiteState::Passed,
             tests: vec![
                 Test {
                     name: "tests::it_should_match_failed".to_string(),
-                    status: "pass".to_string(),
-                    error: None
+                    status: TestStatus::Passed,
+                    error: None,
+                    ..Default::default()
                 },
                 Test {
                     name: "tests::it_should_parse_first_line".to_string(),
-                    status: "pass".to_string(),
-                    error: None
+                    status: TestStatus::Passed,
+                    error: None,
+                    ..Default::default()
                 }
             ],
             passed: 2,
             failed: 0,
             ignored: 0,
             measured: 0,
-            total: 2
+            total: 2,
+            ..Default::default()
         }],
     );
 }
@@ -89,6 +128,8 @@ test result: FAILED. 1 passed; 2 failed; 0 ignored; 0 measured; 0 filtered out
 
 error: test failed";
 
+    assert_same_with_crlf(output);
+
     let x = match cargo_test_result_parser(output) {
         IResult::Done(_, x) => x,
         _ => panic!("BOOM!"),
@@ -99,17 +140,28 @@ error: test failed";
         vec![
             Suite {
                 name: "target/debug/deps/docker_command-be014e20fbd07382".to_string(),
-                state: "pass".to_string(),
+                package: None,
+                crate_name: Some("docker_command".to_string()),
+                kind: SuiteKind::Unknown,
+                source: None,
+                binary: Some("target/debug/deps/docker_command-be014e20fbd07382".to_string()),
+                state: SuiteState::Passed,
                 passed: 0,
                 failed: 0,
                 ignored: 0,
                 measured: 0,
                 total: 0,
-                tests: vec![]
+                tests: vec![],
+                ..Default::default()
             },
             Suite {
                 name: "target/debug/integration_test-d4fc68dd5824cbb9".to_string(),
-                state: "fail".to_string(),
+                package: None,
+                crate_name: Some("integration_test".to_string()),
+                kind: SuiteKind::Unknown,
+                source: None,
+                binary: Some("target/debug/integration_test-d4fc68dd5824cbb9".to_string()),
+                state: SuiteState::Failed,
                 passed: 1,
                 failed: 2,
                 ignored: 0,
@@ -118,20 +170,24 @@ error: test failed";
                 tests: vec![
                     Test {
                         name: "fail".to_string(),
-                        status: "fail".to_string(),
-                        error: Some("thread \'fail\' panicked at \'assertion failed: `(left == right)` (left: `1`, right: `2`)\', tests/integration_test.rs:16".to_string())
+                        status: TestStatus::Failed,
+                        error: Some("thread \'fail\' panicked at \'assertion failed: `(left == right)` (left: `1`, right: `2`)\', tests/integration_test.rs:16".to_string()),
+                        ..Default::default()
                     },
                     Test {
                         name: "fail2".to_string(),
-                        status: "fail".to_string(),
-                        error: Some("thread \'fail2\' panicked at \'assertion failed: `(left == right)` (left: `3`, right: `2`)\', tests/integration_test.rs:22".to_string())
+                        status: TestStatus::Failed,
+                        error: Some("thread \'fail2\' panicked at \'assertion failed: `(left == right)` (left: `3`, right: `2`)\', tests/integration_test.rs:22".to_string()),
+                        ..Default::default()
                     },
                     Test {
                         name: "it_runs_a_command".to_string(),
-                        status: "pass".to_string(),
-                        error: None
+                        status: TestStatus::Passed,
+                        error: None,
+                        ..Default::default()
                     }
-                ]
+                ],
+                ..Default::default()
             }
         ]
     );
@@ -163,22 +219,35 @@ running 0 tests
 
 test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out";
 
+    assert_same_with_crlf(output);
+
     assert_done(
         cargo_test_result_parser(output),
         vec![
             Suite {
                 name: "target/debug/deps/foo-5a7be5d1b9c8e0f6".to_string(),
-                state: "pass".to_string(),
+                package: Some("foo".to_string()),
+                crate_name: Some("foo".to_string()),
+                kind: SuiteKind::Unknown,
+                source: None,
+                binary: Some("target/debug/deps/foo-5a7be5d1b9c8e0f6".to_string()),
+                state: SuiteState::Passed,
                 passed: 0,
                 failed: 0,
                 ignored: 0,
                 measured: 0,
                 total: 0,
-                tests: vec![]
+                tests: vec![],
+                ..Default::default()
             },
             Suite {
                 name: "target/debug/integration_test-283604d1063344ba".to_string(),
-                state: "pass".to_string(),
+                package: Some("foo".to_string()),
+                crate_name: Some("integration_test".to_string()),
+                kind: SuiteKind::Unknown,
+                source: None,
+                binary: Some("target/debug/integration_test-283604d1063344ba".to_string()),
+                state: SuiteState::Passed,
                 passed: 1,
                 failed: 0,
                 ignored: 0,
@@ -187,20 +256,28 @@ test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out";
                 tests: vec![
                     Test {
                         name: "it_runs_a_command".to_string(),
-                        status: "pass".to_string(),
-                        error: None
+                        status: TestStatus::Passed,
+                        error: None,
+                        ..Default::default()
                     }
-                ]
+                ],
+                ..Default::default()
             },
             Suite {
                 name: "foo".to_string(),
-                state: "pass".to_string(),
+                package: Some("foo".to_string()),
+                crate_name: Some("foo".to_string()),
+                kind: SuiteKind::Doc,
+                source: None,
+                binary: None,
+                state: SuiteState::Passed,
                 passed: 0,
                 failed: 0,
                 ignored: 0,
                 measured: 0,
                 total: 0,
-                tests: vec![]
+                tests: vec![],
+                ..Default::default()
             }
         ],
     );
@@ -312,12 +389,19 @@ test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out
 
     ";
 
+    assert_same_with_crlf(output);
+
     assert_done(
         cargo_test_result_parser(output),
         vec![
             Suite {
                 name: "target/debug/deps/libzfs_sys-a797c24cd4b4a7ea".to_string(),
-                state: "pass".to_string(),
+                package: Some("libzfs-sys".to_string()),
+                crate_name: Some("libzfs_sys".to_string()),
+                kind: SuiteKind::Unknown,
+                source: None,
+                binary: Some("target/debug/deps/libzfs_sys-a797c24cd4b4a7ea".to_string()),
+                state: SuiteState::Passed,
                 passed: 3,
                 failed: 0,
                 ignored: 0,
@@ -326,30 +410,40 @@ test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out
                 tests: vec![
                     Test {
                         name: "bindgen_test_layout_zpool_handle".to_string(),
-                        status: "pass".to_string(),
-                        error: None
+                        status: TestStatus::Passed,
+                        error: None,
+                        ..Default::default()
                     },
                     Test {
                         name: "tests::open_close_handle".to_string(),
-                        status: "pass".to_string(),
-                        error: None
+                        status: TestStatus::Passed,
+                        error: None,
+                        ..Default::default()
                     },
                     Test {
                         name: "tests::pool_search_import_list_export".to_string(),
-                        status: "pass".to_string(),
-                        error: None
+                        status: TestStatus::Passed,
+                        error: None,
+                        ..Default::default()
                     }
-                ]
+                ],
+                ..Default::default()
             },
             Suite {
                 name: "libzfs-sys".to_string(),
-                state: "pass".to_string(),
+                package: Some("libzfs-sys".to_string()),
+                crate_name: Some("libzfs_sys".to_string()),
+                kind: SuiteKind::Doc,
+                source: None,
+                binary: None,
+                state: SuiteState::Passed,
                 passed: 0,
                 failed: 0,
                 ignored: 0,
                 measured: 0,
                 total: 0,
-                tests: vec![]
+                tests: vec![],
+                ..Default::default()
             }
         ],
     );
@@ -496,12 +590,14 @@ error: Could not compile `libzfs`.
 To learn more, run the command again with --verbose.
 ";
 
+    assert_same_with_crlf(output);
+
     assert_done(
         cargo_test_result_parser(output),
         vec![
             Suite {
                 name: "unknown".to_string(),
-                state: "fail".to_string(),
+                state: SuiteState::Failed,
                 passed: 0,
                 failed: 1,
                 ignored: 0,
@@ -510,7 +606,7 @@ To learn more, run the command again with --verbose.
                 tests: vec![
                     Test {
                         name: "compile failed".to_string(),
-                        status: "fail".to_string(),
+                        status: TestStatus::Failed,
                         error: Some("binary operation `==` cannot be applied to type `std::result::Result<nvpair::NvData, std::io::Error>`
    --> libzfs/src/lib.rs:134:9
     |
@@ -519,9 +615,11 @@ To learn more, run the command again with --verbose.
     |
     = note: an implementation of `std::cmp::PartialEq` might be missing for `std::result::Result<nvpair::NvData, std::io::Error>`
     = note: this error originates in a macro outside of the current crate
-".to_string())
+".to_string()),
+                        ..Default::default()
                     },
-                ]
+                ],
+                ..Default::default()
             }
         ],
     );
@@ -706,11 +804,18 @@ test router::handlers::tests::test_success ... ok
 test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out
 ";
 
+    assert_same_with_crlf(output);
+
     assert_done(
         cargo_test_result_parser(output),
         vec![Suite {
             name: "target\\debug\\deps\\app-622eefdc86aa5319.exe".to_string(),
-            state: "pass".to_string(),
+            package: Some("app".to_string()),
+            crate_name: Some("app".to_string()),
+            kind: SuiteKind::Unknown,
+            source: None,
+            binary: Some("target\\debug\\deps\\app-622eefdc86aa5319.exe".to_string()),
+            state: SuiteState::Passed,
             passed: 1,
             failed: 0,
             ignored: 0,
@@ -718,9 +823,696 @@ test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out
             total: 1,
             tests: vec![Test {
                 name: "router::handlers::tests::test_success".to_string(),
-                status: "pass".to_string(),
+                status: TestStatus::Passed,
                 error: None,
+                ..Default::default()
             }],
+            ..Default::default()
         }],
     );
 }
+
+#[test]
+fn test_json_message_format_run() {
+    let output = br#"{"reason":"compiler-artifact","package_id":"registry+https://github.com/rust-lang/crates.io-index#libc@0.2.65","manifest_path":"/home/ci/.cargo/registry/src/libc-0.2.65/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"libc","src_path":"/home/ci/.cargo/registry/src/libc-0.2.65/src/lib.rs","edition":"2015","doc":true,"doctest":true,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":false},"features":[],"filenames":["/work/app/target/debug/deps/liblibc-1c2a3f4b5d6e7f80.rlib"],"executable":null,"fresh":true}
+{"reason":"build-script-executed","package_id":"path+file:///work/app#0.1.0","linked_libs":[],"linked_paths":[],"cfgs":[],"env":[],"out_dir":"/work/app/target/debug/build/app-0123456789abcdef/out"}
+{"reason":"compiler-artifact","package_id":"path+file:///work/app#0.1.0","manifest_path":"/work/app/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"app","src_path":"/work/app/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":true},"features":[],"filenames":["/work/app/target/debug/deps/app-622eefdc86aa5319"],"executable":"/work/app/target/debug/deps/app-622eefdc86aa5319","fresh":false}
+{"reason":"compiler-artifact","package_id":"path+file:///work/app#0.1.0","manifest_path":"/work/app/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"app","src_path":"/work/app/src/lib.rs","edition":"2021","doc":true,"doctest":true,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":false},"features":[],"filenames":["/work/app/target/debug/deps/libapp-5a7be5d1b9c8e0f6.rlib"],"executable":null,"fresh":false}
+{"reason":"compiler-artifact","package_id":"path+file:///work/app#0.1.0","manifest_path":"/work/app/Cargo.toml","target":{"kind":["test"],"crate_types":["bin"],"name":"api","src_path":"/work/app/tests/api.rs","edition":"2021","doc":false,"doctest":false,"test":true},"profile":{"opt_level":"0","debuginfo":2,"debug_assertions":true,"overflow_checks":true,"test":true},"features":[],"filenames":["/work/app/target/debug/deps/api-d4fc68dd5824cbb9"],"executable":"/work/app/target/debug/deps/api-d4fc68dd5824cbb9","fresh":false}
+{"reason":"build-finished","success":true}
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 1.02s
+     Running unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+
+running 1 test
+test router::tests::test_success ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+   Doc-tests app
+
+running 0 tests
+
+test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s
+
+"#;
+
+    let run = match cargo_json_parser(output) {
+        IResult::Done(_, run) => run,
+        other => panic!("unexpected result {:?}", other),
+    };
+
+    assert_eq!(run.events.len(), 6);
+    assert_eq!(run.events[5], BuildEvent::BuildFinished { success: true });
+    assert_eq!(run.suites.len(), 2);
+
+    let unit = run.artifact_for(&run.suites[0]).unwrap();
+    assert_eq!(unit.package_name(), "app");
+    assert_eq!(unit.target.kind, vec!["lib".to_string()]);
+    assert!(unit.executable.is_some());
+
+    let doc = run.artifact_for(&run.suites[1]).unwrap();
+    assert_eq!(doc.package_name(), "app");
+    assert_eq!(doc.executable, None);
+}
+
+#[test]
+fn test_json_message_format_compile_fail() {
+    let output = br#"{"reason":"compiler-message","package_id":"path+file:///vagrant/libzfs#0.1.0","manifest_path":"/vagrant/libzfs/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"libzfs","src_path":"/vagrant/libzfs/src/lib.rs","edition":"2015","doc":true,"doctest":true,"test":true},"message":{"rendered":"error[E0369]: binary operation `==` cannot be applied to type `std::result::Result<nvpair::NvData, std::io::Error>`\n   --> libzfs/src/lib.rs:134:9\n","children":[],"code":{"code":"E0369","explanation":null},"level":"error","message":"binary operation `==` cannot be applied to type `std::result::Result<nvpair::NvData, std::io::Error>`","spans":[{"byte_end":4512,"byte_start":4420,"column_end":101,"column_start":9,"expansion":null,"file_name":"libzfs/src/lib.rs","is_primary":true,"label":null,"line_end":134,"line_start":134,"suggested_replacement":null,"suggestion_applicability":null,"text":[]}]}}
+{"reason":"compiler-message","package_id":"path+file:///vagrant/libzfs#0.1.0","manifest_path":"/vagrant/libzfs/Cargo.toml","target":{"kind":["lib"],"crate_types":["lib"],"name":"libzfs","src_path":"/vagrant/libzfs/src/lib.rs","edition":"2015","doc":true,"doctest":true,"test":true},"message":{"rendered":"error: aborting due to 1 previous error\n\n","children":[],"code":null,"level":"error","message":"aborting due to 1 previous error","spans":[]}}
+{"reason":"build-finished","success":false}
+error: could not compile `libzfs` (lib test) due to 1 previous error
+"#;
+
+    let run = match cargo_json_parser(output) {
+        IResult::Done(_, run) => run,
+        other => panic!("unexpected result {:?}", other),
+    };
+
+    assert_eq!(
+        run.suites,
+        vec![Suite {
+            name: "unknown".to_string(),
+            package: Some("libzfs".to_string()),
+            crate_name: Some("libzfs".to_string()),
+            state: SuiteState::Failed,
+            passed: 0,
+            failed: 1,
+            ignored: 0,
+            measured: 0,
+            total: 1,
+            tests: vec![Test {
+                name: "compile failed".to_string(),
+                status: TestStatus::Failed,
+                error: Some(
+                    "binary operation `==` cannot be applied to type `std::result::Result<nvpair::NvData, std::io::Error>`
+   --> libzfs/src/lib.rs:134:9
+"
+                    .to_string()
+                ),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }]
+    );
+
+    let annotations = run.annotations();
+    assert_eq!(annotations.len(), 1);
+    assert_eq!(annotations[0].title, "error[E0369]");
+    assert_eq!(annotations[0].file.as_deref(), Some("libzfs/src/lib.rs"));
+    assert_eq!((annotations[0].line, annotations[0].column), (Some(134), Some(9)));
+}
+
+#[test]
+fn test_quiet_run() {
+    let output = b"    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.05s
+     Running unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+
+running 5 tests
+..F.i
+failures:
+
+---- tests::it_fails stdout ----
+thread 'tests::it_fails' panicked at 'assertion failed: false', src/lib.rs:12
+
+failures:
+    tests::it_fails
+
+test result: FAILED. 3 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+error: test failed, to rerun pass `--lib`
+";
+
+    assert_same_with_crlf(output);
+
+    let x = match cargo_test_result_parser(output) {
+        IResult::Done(_, x) => x,
+        other => panic!("unexpected result {:?}", other),
+    };
+
+    assert_eq!(
+        x,
+        vec![Suite {
+            name: "unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)".to_string(),
+            package: None,
+            crate_name: Some("app".to_string()),
+            kind: SuiteKind::Unit,
+            source: Some("src/lib.rs".to_string()),
+            binary: Some("target/debug/deps/app-622eefdc86aa5319".to_string()),
+            duration: Some(0.0),
+            state: SuiteState::Failed,
+            passed: 3,
+            failed: 1,
+            ignored: 1,
+            measured: 0,
+            total: 5,
+            tests: vec![Test {
+                name: "tests::it_fails".to_string(),
+                status: TestStatus::Failed,
+                error: Some("thread 'tests::it_fails' panicked at 'assertion failed: false', src/lib.rs:12".to_string()),
+                ..Default::default()
+            }],
+            anonymous_tests: true,
+            ..Default::default()
+        }]
+    );
+}
+
+#[test]
+fn test_colored_run() {
+    let output = b"\x1b[1m\x1b[32m   Compiling\x1b[0m app v0.1.0 (/work/app)
+\x1b[1m\x1b[36m    Building\x1b[0m [=======================> ] 41/42: app\r\x1b[K\x1b[1m\x1b[32m    Finished\x1b[0m `test` profile [unoptimized + debuginfo] target(s) in 2.31s
+\x1b[1m\x1b[32m     Running\x1b[0m unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+
+running 2 tests
+test tests::it_fails ... \x1b[31mFAILED\x1b[0m
+test tests::it_works ... \x1b[32mok\x1b[0m
+
+failures:
+
+---- tests::it_fails stdout ----
+thread 'tests::it_fails' panicked at \x1b[1m'assertion failed: false'\x1b[0m, src/lib.rs:12
+
+failures:
+    tests::it_fails
+
+test result: \x1b[31mFAILED\x1b[0m. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+";
+
+    let expected = |error: &str| {
+        vec![Suite {
+            name: "unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)".to_string(),
+            package: Some("app".to_string()),
+            crate_name: Some("app".to_string()),
+            kind: SuiteKind::Unit,
+            source: Some("src/lib.rs".to_string()),
+            binary: Some("target/debug/deps/app-622eefdc86aa5319".to_string()),
+            duration: Some(0.0),
+            state: SuiteState::Failed,
+            passed: 1,
+            failed: 1,
+            ignored: 0,
+            measured: 0,
+            total: 2,
+            tests: vec![
+                Test {
+                    name: "tests::it_fails".to_string(),
+                    status: TestStatus::Failed,
+                    error: Some(error.to_string()),
+                    ..Default::default()
+                },
+                Test {
+                    name: "tests::it_works".to_string(),
+                    status: TestStatus::Passed,
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        }]
+    };
+
+    let stripped = strip_ansi(output, Colors::Strip);
+    let x = match cargo_test_result_parser(&stripped) {
+        IResult::Done(_, x) => x,
+        other => panic!("unexpected result {:?}", other),
+    };
+    assert_eq!(
+        x,
+        expected("thread 'tests::it_fails' panicked at 'assertion failed: false', src/lib.rs:12")
+    );
+
+    let preserved = strip_ansi(output, Colors::PreserveInFailures);
+    let x = match cargo_test_result_parser(&preserved) {
+        IResult::Done(_, x) => x,
+        other => panic!("unexpected result {:?}", other),
+    };
+    assert_eq!(
+        x,
+        expected("thread 'tests::it_fails' panicked at \x1b[1m'assertion failed: false'\x1b[0m, src/lib.rs:12")
+    );
+}
+
+#[test]
+fn test_non_utf8_output() {
+    let output = b"    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.05s
+     Running unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+
+running 2 tests
+test tests::it_decodes ... FAILED
+test tests::it_works ... ok
+
+failures:
+
+---- tests::it_decodes stdout ----
+read \xff\xfe from libiconv: Ol\xe9
+thread 'tests::it_decodes' panicked at 'assertion failed: false', src/lib.rs:12
+
+failures:
+    tests::it_decodes
+
+test result: FAILED. 1 passed; 1 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+";
+
+    assert_same_with_crlf(output);
+
+    let x = match cargo_test_result_parser(output) {
+        IResult::Done(_, x) => x,
+        other => panic!("unexpected result {:?}", other),
+    };
+
+    assert_eq!(
+        x,
+        vec![Suite {
+            name: "unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)".to_string(),
+            package: None,
+            crate_name: Some("app".to_string()),
+            kind: SuiteKind::Unit,
+            source: Some("src/lib.rs".to_string()),
+            binary: Some("target/debug/deps/app-622eefdc86aa5319".to_string()),
+            duration: Some(0.0),
+            state: SuiteState::Failed,
+            passed: 1,
+            failed: 1,
+            ignored: 0,
+            measured: 0,
+            total: 2,
+            tests: vec![
+                Test {
+                    name: "tests::it_decodes".to_string(),
+                    status: TestStatus::Failed,
+                    error: Some(
+                        "read \u{fffd}\u{fffd} from libiconv: Ol\u{fffd}\n\
+                         thread 'tests::it_decodes' panicked at 'assertion failed: false', src/lib.rs:12"
+                            .to_string()
+                    ),
+                    lossy: true,
+                    ..Default::default()
+                },
+                Test {
+                    name: "tests::it_works".to_string(),
+                    status: TestStatus::Passed,
+                    ..Default::default()
+                },
+            ],
+            lossy: true,
+            ..Default::default()
+        }]
+    );
+}
+
+#[test]
+fn test_tricky_test_names() {
+    let output = "     Running unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+
+running 6 tests
+test tests::with ... inside ... FAILED
+test tests::ellipsis::wait... ... ok
+test tests::ranges::case_0..=10 ... ok
+test tests::größe_berechnen ... ok
+test tests::測試 ... ok
+test tests::slow ... ignored, needs network
+
+failures:
+
+---- tests::with ... inside stdout ----
+thread 'tests::with ... inside' panicked at src/lib.rs:12:9:
+boom
+
+failures:
+    tests::with ... inside
+
+test result: FAILED. 4 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+   Doc-tests app
+
+running 3 tests
+test src/lib.rs - Parser (line 30) ... ignored
+test src/lib.rs - Parser::new (line 40) ... ok
+test src/lib.rs - parse (line 12) ... FAILED
+
+failures:
+
+---- src/lib.rs - parse (line 12) stdout ----
+Test executable failed (exit status: 101).
+
+stderr:
+thread 'main' panicked at src/lib.rs:5:1:
+boom
+
+failures:
+    src/lib.rs - parse (line 12)
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.21s
+
+"
+    .as_bytes();
+
+    assert_same_with_crlf(output);
+
+    let x = match cargo_test_result_parser(output) {
+        IResult::Done(_, x) => x,
+        other => panic!("unexpected result {:?}", other),
+    };
+
+    let names = |suite: &Suite| -> Vec<(String, String)> {
+        suite
+            .tests
+            .iter()
+            .map(|t| (t.name.clone(), t.status.to_string()))
+            .collect()
+    };
+    let expected = |tests: &[(&str, &str)]| -> Vec<(String, String)> {
+        tests
+            .iter()
+            .map(|&(name, status)| (name.to_string(), status.to_string()))
+            .collect()
+    };
+
+    assert_eq!(x.len(), 2);
+    assert_eq!(
+        names(&x[0]),
+        expected(&[
+            ("tests::with ... inside", "fail"),
+            ("tests::ellipsis::wait...", "pass"),
+            ("tests::ranges::case_0..=10", "pass"),
+            ("tests::größe_berechnen", "pass"),
+            ("tests::測試", "pass"),
+            ("tests::slow", "ignored"),
+        ])
+    );
+    assert_eq!(
+        x[0].tests[0].error,
+        Some("thread 'tests::with ... inside' panicked at src/lib.rs:12:9:\nboom".to_string())
+    );
+
+    assert_eq!(x[1].name, "app");
+    assert_eq!(
+        names(&x[1]),
+        expected(&[
+            ("src/lib.rs - Parser (line 30)", "ignored"),
+            ("src/lib.rs - Parser::new (line 40)", "pass"),
+            ("src/lib.rs - parse (line 12)", "fail"),
+        ])
+    );
+    assert_eq!(
+        x[1].tests[2].error,
+        Some(
+            "Test executable failed (exit status: 101).\n\nstderr:\nthread 'main' panicked at src/lib.rs:5:1:\nboom"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn test_workspace_run() {
+    let output = b"   Compiling serde v1.0.102
+   Compiling store-core v0.2.0 (/work/crates/core)
+   Compiling api-server v0.1.0 (/work/crates/api)
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 4.20s
+     Running unittests src/lib.rs (target/debug/deps/api_server-622eefdc86aa5319)
+
+running 1 test
+test routes::it_routes ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+     Running tests/http.rs (target/debug/deps/http-0b1c2d3e4f5a6b7c)
+
+running 1 test
+test it_serves ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.01s
+
+     Running unittests src/lib.rs (target/debug/deps/store_core-1a2b3c4d5e6f7a8b)
+
+running 1 test
+test it_stores ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+   Doc-tests api_server
+
+running 0 tests
+
+test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+   Doc-tests store_core
+
+running 0 tests
+
+test result: ok. 0 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+";
+
+    let x = match cargo_test_result_parser(output) {
+        IResult::Done(_, x) => x,
+        other => panic!("unexpected result {:?}", other),
+    };
+
+    let crates: Vec<Option<&str>> = x.iter().map(|s| s.crate_name.as_deref()).collect();
+    assert_eq!(
+        crates,
+        vec![Some("api_server"), Some("http"), Some("store_core"), Some("api_server"), Some("store_core")]
+    );
+
+    let kinds: Vec<SuiteKind> = x.iter().map(|s| s.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![SuiteKind::Unit, SuiteKind::Integration, SuiteKind::Unit, SuiteKind::Doc, SuiteKind::Doc]
+    );
+    assert_eq!(x[1].source, Some("tests/http.rs".to_string()));
+    assert_eq!(x[1].binary, Some("target/debug/deps/http-0b1c2d3e4f5a6b7c".to_string()));
+
+    let groups: Vec<(Option<&str>, Vec<&str>)> = group_by_package(&x)
+        .into_iter()
+        .map(|(package, suites)| (package, suites.iter().map(|s| s.name.as_str()).collect()))
+        .collect();
+    assert_eq!(
+        groups,
+        vec![
+            (
+                Some("api-server"),
+                vec![
+                    "unittests src/lib.rs (target/debug/deps/api_server-622eefdc86aa5319)",
+                    "tests/http.rs (target/debug/deps/http-0b1c2d3e4f5a6b7c)",
+                    "api_server",
+                ]
+            ),
+            (
+                Some("store-core"),
+                vec![
+                    "unittests src/lib.rs (target/debug/deps/store_core-1a2b3c4d5e6f7a8b)",
+                    "store_core",
+                ]
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_github_actions_log() {
+    let output = b"2026-01-01T12:00:00.0000000Z    Compiling app v0.1.0 (/home/runner/work/app/app)
+2026-01-01T12:00:04.0000000Z     Finished `test` profile [unoptimized + debuginfo] target(s) in 4.00s
+2026-01-01T12:00:04.5000000Z      Running unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+2026-01-01T12:00:04.5000000Z 
+2026-01-01T12:00:04.5000000Z running 2 tests
+2026-01-01T12:00:05.0000000Z test tests::it_works ... ok
+2026-01-01T12:00:07.2500000Z test tests::it_is_slow ... ok
+2026-01-01T12:00:07.2500000Z 
+2026-01-01T12:00:07.5000000Z test result: ok. 2 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 2.75s
+2026-01-01T12:00:07.5000000Z 
+";
+
+    let log = strip_ci_prefixes(output, &[CiPrefix::GithubActions]);
+
+    let x = match cargo_test_result_parser(&log.text) {
+        IResult::Done(_, x) => x,
+        other => panic!("unexpected result {:?}", other),
+    };
+
+    assert_eq!(x.len(), 1);
+    assert_eq!(x[0].package, Some("app".to_string()));
+    assert_eq!(x[0].passed, 2);
+
+    assert_eq!(
+        log.suite_timings(),
+        vec![Timing {
+            name: x[0].name.clone(),
+            seconds: 3.0,
+        }]
+    );
+    assert_eq!(
+        log.test_timings(),
+        vec![
+            Timing {
+                name: "tests::it_works".to_string(),
+                seconds: 0.5,
+            },
+            Timing {
+                name: "tests::it_is_slow".to_string(),
+                seconds: 2.25,
+            },
+        ]
+    );
+}
+
+// A current `cargo test` run with a failure, an ignored test and doc
+// tests, written out by every report format.
+const REPORT_RUN: &[u8] = b"   Compiling app v0.1.0 (/home/user/app)
+    Finished `test` profile [unoptimized + debuginfo] target(s) in 0.52s
+     Running unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+
+running 3 tests
+test tests::it_is_slow ... ignored
+test tests::it_works ... ok
+test tests::it_fails ... FAILED
+
+failures:
+
+---- tests::it_fails stdout ----
+
+thread 'tests::it_fails' panicked at src/lib.rs:12:9:
+assertion `left == right` failed
+  left: 1
+ right: 2
+note: run with `RUST_BACKTRACE=1` environment variable to display a backtrace
+
+
+failures:
+    tests::it_fails
+
+test result: FAILED. 1 passed; 1 failed; 1 ignored; 0 measured; 0 filtered out; finished in 0.00s
+
+   Doc-tests app
+
+running 1 test
+test src/lib.rs - add (line 3) ... ok
+
+test result: ok. 1 passed; 0 failed; 0 ignored; 0 measured; 0 filtered out; finished in 0.21s
+
+error: test failed, to rerun pass `--lib`
+";
+
+fn report_suites() -> Vec<Suite> {
+    parse(REPORT_RUN).unwrap().suites
+}
+
+#[test]
+fn test_junit_report() {
+    let original = report_suites();
+    let mut output = vec![];
+    junit_xml_writer(&original, &mut output).unwrap();
+
+    let xml = String::from_utf8(output).unwrap();
+    assert!(xml.contains(
+        "<failure type=\"test failure\" message=\"assertion `left == right` failed&#xA;  left: 1&#xA; right: 2\">"
+    ));
+
+    let suites = junit_xml_parser(xml.as_bytes()).unwrap();
+    assert_eq!(suites.len(), 2);
+    assert_eq!((suites[0].passed, suites[0].failed, suites[0].ignored), (1, 1, 1));
+    for (suite, original) in suites.iter().zip(&original) {
+        let tests = |s: &Suite| -> Vec<(String, TestStatus, Option<String>)> {
+            s.tests.iter().map(|t| (t.name.clone(), t.status, t.error.clone())).collect()
+        };
+        assert_eq!(suite.name, original.name);
+        assert_eq!(tests(suite), tests(original));
+    }
+}
+
+#[test]
+fn test_tap_report() {
+    let mut output = vec![];
+    tap_writer(&report_suites(), &mut output).unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "TAP version 14
+1..2
+# Subtest: unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+    1..3
+    ok 1 - tests::it_is_slow # SKIP
+    ok 2 - tests::it_works
+    not ok 3 - tests::it_fails
+      ---
+      message: \"assertion `left == right` failed\\n  left: 1\\n right: 2\"
+      severity: fail
+      at:
+        file: \"src/lib.rs\"
+        line: 12
+        column: 9
+      output: |2-
+        thread 'tests::it_fails' panicked at src/lib.rs:12:9:
+        assertion `left == right` failed
+          left: 1
+         right: 2
+      ...
+not ok 1 - unittests src/lib.rs (target/debug/deps/app-622eefdc86aa5319)
+# Subtest: app
+    1..1
+    ok 1 - src/lib.rs - add (line 3)
+ok 2 - app
+"
+    );
+}
+
+#[test]
+fn test_markdown_report() {
+    let mut output = vec![];
+    markdown_writer(
+        &report_suites(),
+        Some("https://github.com/owner/app/blob/main/{file}#L{line}"),
+        &mut output,
+    )
+    .unwrap();
+
+    let markdown = String::from_utf8(output).unwrap();
+    assert!(markdown.contains("| 2 | 2 | 1 | 1 | 0 | 66.7% |"));
+    assert!(markdown.contains("| ✅ | `app` | 1 | 0 | 0 | 0.21s |"));
+    assert!(markdown.contains("at [`src/lib.rs:12:9`](https://github.com/owner/app/blob/main/src/lib.rs#L12)"));
+    assert!(markdown.contains("```text\nassertion `left == right` failed\n  left: 1\n right: 2\n```"));
+}
+
+#[test]
+fn test_github_annotations() {
+    let mut output = vec![];
+    annotation_writer(&annotations(&report_suites()), AnnotationFormat::GithubActions, &mut output).unwrap();
+
+    assert_eq!(
+        String::from_utf8(output).unwrap(),
+        "::error file=src/lib.rs,line=12,col=9,title=app/unit/app@src/lib.rs%3A%3Atests%3A%3Ait_fails failed::\
+         assertion `left == right` failed%0A  left: 1%0A right: 2\n"
+    );
+}
+
+#[test]
+fn test_sarif_report() {
+    let mut output = vec![];
+    sarif_writer(&report_suites(), &[], &mut output).unwrap();
+
+    let sarif: serde_json::Value = serde_json::from_slice(&output).unwrap();
+    let results = sarif["runs"][0]["results"].as_array().unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0]["ruleId"], "test-failure");
+    assert_eq!(results[0]["message"]["text"], "assertion `left == right` failed\n  left: 1\n right: 2");
+    assert_eq!(results[0]["locations"][0]["physicalLocation"]["region"]["startLine"], 12);
+    assert_eq!(
+        results[0]["logicalLocations"][0]["fullyQualifiedName"],
+        "app/unit/app@src/lib.rs::tests::it_fails"
+    );
+}
+
+#[test]
+fn test_html_report() {
+    let mut output = vec![];
+    html_writer(&report_suites(), &mut output).unwrap();
+
+    let html = String::from_utf8(output).unwrap();
+    assert!(html.contains("<p>2 suites: 2 passed, 1 failed, 1 ignored, 0 measured</p>"));
+    assert!(html.contains(
+        "<pre class=\"output\">thread &#39;tests::it_fails&#39; panicked at src/lib.rs:12:9:\n\
+         assertion `left == right` failed\n  left: 1\n right: 2</pre>"
+    ));
+    assert!(html.contains("<code>app</code>"));
+}